@@ -0,0 +1,231 @@
+//! Custom derive for [`seckey::ZeroSafe`](https://docs.rs/seckey/*/seckey/trait.ZeroSafe.html).
+//!
+//! ```
+//! #[macro_use] extern crate seckey_derive;
+//! extern crate seckey;
+//!
+//! use seckey::{ ZeroSafe, zero };
+//!
+//! #[derive(ZeroSafe)]
+//! struct Keys {
+//!     enc_key: [u8; 32],
+//!     mac_key: [u8; 32],
+//!     counter: u64
+//! }
+//!
+//! # fn main() {
+//! let mut keys = Keys { enc_key: [1; 32], mac_key: [2; 32], counter: 3 };
+//! zero(&mut keys);
+//! assert_eq!(keys.enc_key, [0; 32]);
+//! assert_eq!(keys.counter, 0);
+//! # }
+//! ```
+//!
+//! Fields must be `ZeroSafe` themselves,
+//! references, pointers and `NonZero*` are rejected:
+//!
+//! ```compile_fail
+//! #[macro_use] extern crate seckey_derive;
+//! extern crate seckey;
+//!
+//! #[derive(ZeroSafe)]
+//! struct Borrowed<'a>(&'a [u8]);
+//! # fn main() {}
+//! ```
+//!
+//! ```compile_fail
+//! #[macro_use] extern crate seckey_derive;
+//! extern crate seckey;
+//!
+//! #[derive(ZeroSafe)]
+//! struct Counter(std::num::NonZeroU64);
+//! # fn main() {}
+//! ```
+//!
+//! ```compile_fail
+//! #[macro_use] extern crate seckey_derive;
+//! extern crate seckey;
+//!
+//! #[derive(ZeroSafe)]
+//! struct Flag(bool);
+//! # fn main() {}
+//! ```
+//!
+//! Enums must be fieldless, have an integer `repr` and a variant with discriminant `0`:
+//!
+//! ```
+//! #[macro_use] extern crate seckey_derive;
+//! extern crate seckey;
+//!
+//! #[derive(ZeroSafe)]
+//! #[repr(u8)]
+//! enum Mode { Idle, Encrypt, Decrypt }
+//! # fn main() {}
+//! ```
+//!
+//! ```compile_fail
+//! #[macro_use] extern crate seckey_derive;
+//! extern crate seckey;
+//!
+//! #[derive(ZeroSafe)]
+//! #[repr(u8)]
+//! enum Mode { Encrypt = 1, Decrypt = 2 }
+//! # fn main() {}
+//! ```
+
+extern crate proc_macro;
+extern crate proc_macro2;
+extern crate syn;
+#[macro_use] extern crate quote;
+
+use proc_macro::TokenStream;
+use syn::{
+    Data, DeriveInput, Error, Expr, Fields, Lit, Type, UnOp,
+    parse_macro_input, parse_quote
+};
+use syn::spanned::Spanned;
+
+
+#[proc_macro_derive(ZeroSafe)]
+pub fn derive_zerosafe(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(compile_error)
+        .into()
+}
+
+/// Like `Error::into_compile_error`, without the `::core` path
+/// that 2015 edition crates can not resolve.
+fn compile_error(err: Error) -> proc_macro2::TokenStream {
+    err.into_iter()
+        .map(|err| {
+            let msg = err.to_string();
+            quote_spanned!{ err.span() => compile_error!(#msg); }
+        })
+        .collect()
+}
+
+fn expand(mut input: DeriveInput) -> Result<proc_macro2::TokenStream, Error> {
+    let fields = match input.data {
+        Data::Struct(ref data) => field_types(&data.fields),
+        Data::Enum(_) => {
+            check_enum(&input)?;
+            Vec::new()
+        },
+        Data::Union(_) => return Err(Error::new(
+            input.span(),
+            "ZeroSafe can not be derived for unions"
+        ))
+    };
+
+    for ty in &fields {
+        check_type(ty)?;
+    }
+
+    {
+        let where_clause = input.generics.make_where_clause();
+        for ty in &fields {
+            where_clause.predicates.push(parse_quote!{ #ty: ::seckey::ZeroSafe });
+        }
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote!{
+        unsafe impl #impl_generics ::seckey::ZeroSafe for #name #ty_generics #where_clause {}
+    })
+}
+
+fn field_types(fields: &Fields) -> Vec<Type> {
+    fields.iter()
+        .map(|field| field.ty.clone())
+        .collect()
+}
+
+/// Reject types whose all-zero bit pattern is never valid.
+fn check_type(ty: &Type) -> Result<(), Error> {
+    match *ty {
+        Type::Reference(_) => Err(Error::new(ty.span(), "ZeroSafe field can not be a reference")),
+        Type::Ptr(_) => Err(Error::new(ty.span(), "ZeroSafe field can not be a pointer")),
+        Type::BareFn(_) => Err(Error::new(ty.span(), "ZeroSafe field can not be a function pointer")),
+        Type::Array(ref ty) => check_type(&ty.elem),
+        Type::Slice(ref ty) => check_type(&ty.elem),
+        Type::Paren(ref ty) => check_type(&ty.elem),
+        Type::Group(ref ty) => check_type(&ty.elem),
+        Type::Tuple(ref ty) => ty.elems.iter().try_for_each(check_type),
+        Type::Path(ref ty) => match ty.path.segments.last() {
+            Some(segment) if segment.ident.to_string().starts_with("NonZero") =>
+                Err(Error::new(ty.span(), "ZeroSafe field can not be `NonZero*`")),
+            Some(segment) if segment.ident == "NonNull" =>
+                Err(Error::new(ty.span(), "ZeroSafe field can not be a pointer")),
+            _ => Ok(())
+        },
+        _ => Ok(())
+    }
+}
+
+/// Accept only fieldless enums with an integer repr and a zero discriminant.
+fn check_enum(input: &DeriveInput) -> Result<(), Error> {
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => unreachable!()
+    };
+
+    let mut has_repr = false;
+    for attr in input.attrs.iter().filter(|attr| attr.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            const INT_REPR: &[&str] = &[
+                "u8", "u16", "u32", "u64", "u128", "usize",
+                "i8", "i16", "i32", "i64", "i128", "isize",
+                "C"
+            ];
+
+            if INT_REPR.iter().any(|repr| meta.path.is_ident(repr)) {
+                has_repr = true;
+            }
+            Ok(())
+        })?;
+    }
+
+    if !has_repr {
+        return Err(Error::new(input.span(), "ZeroSafe enum must have an integer `repr`"));
+    }
+
+    let mut next = 0;
+    let mut has_zero = false;
+    for variant in &data.variants {
+        if !variant.fields.is_empty() {
+            return Err(Error::new(variant.span(), "ZeroSafe enum must be fieldless"));
+        }
+
+        let discriminant = match variant.discriminant {
+            Some((_, ref expr)) => eval_discriminant(expr)?,
+            None => next
+        };
+
+        has_zero |= discriminant == 0;
+        next = discriminant + 1;
+    }
+
+    if has_zero {
+        Ok(())
+    } else {
+        Err(Error::new(input.span(), "ZeroSafe enum must have a variant with discriminant 0"))
+    }
+}
+
+fn eval_discriminant(expr: &Expr) -> Result<i128, Error> {
+    match *expr {
+        Expr::Lit(ref expr) => match expr.lit {
+            Lit::Int(ref lit) => lit.base10_parse(),
+            _ => Err(Error::new(expr.span(), "discriminant must be an integer literal"))
+        },
+        Expr::Unary(ref expr) if matches!(expr.op, UnOp::Neg(_)) =>
+            eval_discriminant(&expr.expr).map(|n| -n),
+        Expr::Paren(ref expr) => eval_discriminant(&expr.expr),
+        Expr::Group(ref expr) => eval_discriminant(&expr.expr),
+        _ => Err(Error::new(expr.span(), "discriminant must be an integer literal"))
+    }
+}
@@ -0,0 +1,115 @@
+//! The `memsec` canary in front of each key.
+//!
+//! `memsec::malloc` right-aligns the value against a guard page,
+//! so an overflow faults at once, but an underflow only overwrites the canary,
+//! and `memsec::free` aborts without saying which key it was.
+//! Debug builds check it whenever a key is locked again.
+
+#[cfg(debug_assertions)] use core::any;
+use core::ptr::{ self, NonNull };
+#[cfg(debug_assertions)] use core::panic::Location;
+use std::{ eprintln, process };
+use std::sync::OnceLock;
+
+
+const CANARY_SIZE: usize = 16;
+
+/// `memsec` writes the same random canary in front of every allocation.
+static EXPECTED: OnceLock<[u8; CANARY_SIZE]> = OnceLock::new();
+
+/// Where a key was created.
+#[cfg(debug_assertions)]
+#[derive(Clone, Copy)]
+pub(crate) struct Origin {
+    location: &'static Location<'static>,
+    type_name: &'static str
+}
+
+#[cfg(debug_assertions)]
+impl Origin {
+    #[track_caller]
+    #[inline]
+    pub(crate) fn new<T: ?Sized>() -> Origin {
+        Origin {
+            location: Location::caller(),
+            type_name: any::type_name::<T>()
+        }
+    }
+
+    /// Same creation site, viewed as another type.
+    #[inline]
+    pub(crate) fn cast<U: ?Sized>(self) -> Origin {
+        Origin { type_name: any::type_name::<U>(), ..self }
+    }
+}
+
+#[inline]
+fn canary_ptr<T: ?Sized>(ptr: NonNull<T>) -> *mut [u8; CANARY_SIZE] {
+    unsafe { ptr.cast::<u8>().as_ptr().sub(CANARY_SIZE) as *mut [u8; CANARY_SIZE] }
+}
+
+/// Remember the canary of a fresh allocation, before it is handed out.
+pub(crate) unsafe fn record<T: ?Sized>(ptr: NonNull<T>) {
+    EXPECTED.get_or_init(|| ptr::read_unaligned(canary_ptr(ptr)));
+}
+
+/// The key was zeroed in a child process, canary included.
+///
+/// The canary must be readable.
+#[inline]
+pub(crate) unsafe fn is_wiped<T: ?Sized>(ptr: NonNull<T>) -> bool {
+    ptr::read_unaligned(canary_ptr(ptr)) == [0; CANARY_SIZE]
+}
+
+/// Abort if the key was zeroed in a child process, the value may not be valid.
+///
+/// The canary must be readable.
+pub(crate) unsafe fn forbid_wiped<T: ?Sized>(ptr: NonNull<T>) {
+    if is_wiped(ptr) {
+        eprintln!(
+            "seckey: a key zeroed in a child process was accessed, it can only be dropped\n  \
+             address: {:p}",
+            ptr
+        );
+        process::abort();
+    }
+}
+
+/// Put back a canary zeroed in a child process, so `memsec::free` accepts it.
+///
+/// Returns `true` if the key was wiped. The canary must be writable.
+pub(crate) unsafe fn restore_wiped<T: ?Sized>(ptr: NonNull<T>) -> bool {
+    match EXPECTED.get() {
+        Some(expected) if is_wiped(ptr) => {
+            ptr::write_unaligned(canary_ptr(ptr), *expected);
+            true
+        },
+        _ => false
+    }
+}
+
+/// Abort with a report if the canary was overwritten.
+///
+/// The canary must be readable, ie. the key is unlocked.
+/// A zeroed canary passes if `wiped` is allowed.
+#[cfg(debug_assertions)]
+pub(crate) unsafe fn check<T: ?Sized>(ptr: NonNull<T>, origin: &Origin, wiped: bool) {
+    let expected = match EXPECTED.get() {
+        Some(expected) => expected,
+        None => return
+    };
+
+    let canary = ptr::read_unaligned(canary_ptr(ptr));
+    if canary != *expected && !(wiped && canary == [0; CANARY_SIZE]) {
+        eprintln!(
+            "seckey: canary check failed, memory in front of a key was overwritten\n  \
+             type:       {}\n  \
+             created at: {}\n  \
+             address:    {:p}",
+            origin.type_name,
+            origin.location,
+            ptr
+        );
+        process::abort();
+    }
+}
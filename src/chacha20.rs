@@ -0,0 +1,136 @@
+//! ChaCha20 keystream, [RFC 8439](https://tools.ietf.org/html/rfc8439).
+//!
+//! Only used to seal idle keys, so it favors size over speed.
+
+use ::zero;
+
+
+pub(crate) const KEY_LENGTH: usize = 32;
+pub(crate) const NONCE_LENGTH: usize = 12;
+
+const SIGMA: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+
+#[inline(always)]
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]); state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]); state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]); state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]); state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+#[inline]
+fn read_u32(input: &[u8]) -> u32 {
+    u32::from(input[0])
+        | u32::from(input[1]) << 8
+        | u32::from(input[2]) << 16
+        | u32::from(input[3]) << 24
+}
+
+fn block(key: &[u8; KEY_LENGTH], counter: u32, nonce: &[u8; NONCE_LENGTH], output: &mut [u8; 64]) {
+    let mut init = [0u32; 16];
+    init[..4].copy_from_slice(&SIGMA);
+    for (i, chunk) in key.chunks(4).enumerate() {
+        init[4 + i] = read_u32(chunk);
+    }
+    init[12] = counter;
+    for (i, chunk) in nonce.chunks(4).enumerate() {
+        init[13 + i] = read_u32(chunk);
+    }
+
+    let mut state = init;
+    for _ in 0..10 {
+        quarter_round(&mut state, 0, 4, 8, 12);
+        quarter_round(&mut state, 1, 5, 9, 13);
+        quarter_round(&mut state, 2, 6, 10, 14);
+        quarter_round(&mut state, 3, 7, 11, 15);
+        quarter_round(&mut state, 0, 5, 10, 15);
+        quarter_round(&mut state, 1, 6, 11, 12);
+        quarter_round(&mut state, 2, 7, 8, 13);
+        quarter_round(&mut state, 3, 4, 9, 14);
+    }
+
+    for (i, chunk) in output.chunks_mut(4).enumerate() {
+        let word = state[i].wrapping_add(init[i]);
+        chunk[0] = word as u8;
+        chunk[1] = (word >> 8) as u8;
+        chunk[2] = (word >> 16) as u8;
+        chunk[3] = (word >> 24) as u8;
+    }
+
+    zero(&mut init);
+    zero(&mut state);
+}
+
+/// XOR `data` with the keystream, starting at block counter `0`.
+pub(crate) fn apply_keystream(key: &[u8; KEY_LENGTH], nonce: &[u8; NONCE_LENGTH], data: &mut [u8]) {
+    let mut keystream = [0; 64];
+
+    for (counter, chunk) in data.chunks_mut(64).enumerate() {
+        block(key, counter as u32, nonce, &mut keystream);
+        for (byte, k) in chunk.iter_mut().zip(keystream.iter()) {
+            *byte ^= k;
+        }
+    }
+
+    zero(&mut keystream);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use std::vec;
+    use super::apply_keystream;
+
+
+    const KEY: [u8; 32] = [
+        0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f
+    ];
+
+    /// RFC 8439, 2.3.2
+    #[test]
+    fn chacha20_block_test() {
+        let nonce = [0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00];
+        let expected = [
+            0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15, 0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20, 0x71, 0xc4,
+            0xc7, 0xd1, 0xf4, 0xc7, 0x33, 0xc0, 0x68, 0x03, 0x04, 0x22, 0xaa, 0x9a, 0xc3, 0xd4, 0x6c, 0x4e,
+            0xd2, 0x82, 0x64, 0x46, 0x07, 0x9f, 0xaa, 0x09, 0x14, 0xc2, 0xd7, 0x05, 0xd9, 0x8b, 0x02, 0xa2,
+            0xb5, 0x12, 0x9c, 0xd1, 0xde, 0x16, 0x4e, 0xb9, 0xcb, 0xd0, 0x83, 0xe8, 0xa2, 0x50, 0x3c, 0x4e
+        ];
+
+        // the keystream starts at block 0, the test vector is block 1
+        let mut data = [0; 128];
+        apply_keystream(&KEY, &nonce, &mut data);
+
+        assert_eq!(&data[64..], &expected[..]);
+    }
+
+    /// RFC 8439, 2.4.2
+    #[test]
+    fn chacha20_encrypt_test() {
+        let nonce = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00];
+        let plaintext = b"Ladies and Gentlemen of the class of '99: \
+            If I could offer you only one tip for the future, sunscreen would be it.";
+        let expected = [
+            0x6e, 0x2e, 0x35, 0x9a, 0x25, 0x68, 0xf9, 0x80, 0x41, 0xba, 0x07, 0x28, 0xdd, 0x0d, 0x69, 0x81,
+            0xe9, 0x7e, 0x7a, 0xec, 0x1d, 0x43, 0x60, 0xc2, 0x0a, 0x27, 0xaf, 0xcc, 0xfd, 0x9f, 0xae, 0x0b,
+            0xf9, 0x1b, 0x65, 0xc5, 0x52, 0x47, 0x33, 0xab, 0x8f, 0x59, 0x3d, 0xab, 0xcd, 0x62, 0xb3, 0x57,
+            0x16, 0x39, 0xd6, 0x24, 0xe6, 0x51, 0x52, 0xab, 0x8f, 0x53, 0x0c, 0x35, 0x9f, 0x08, 0x61, 0xd8,
+            0x07, 0xca, 0x0d, 0xbf, 0x50, 0x0d, 0x6a, 0x61, 0x56, 0xa3, 0x8e, 0x08, 0x8a, 0x22, 0xb6, 0x5e,
+            0x52, 0xbc, 0x51, 0x4d, 0x16, 0xcc, 0xf8, 0x06, 0x81, 0x8c, 0xe9, 0x1a, 0xb7, 0x79, 0x37, 0x36,
+            0x5a, 0xf9, 0x0b, 0xbf, 0x74, 0xa3, 0x5b, 0xe6, 0xb4, 0x0b, 0x8e, 0xed, 0xf2, 0x78, 0x5e, 0x42,
+            0x87, 0x4d
+        ];
+
+        // skip block 0, the test vector starts at block 1
+        let mut data = vec![0; 64];
+        data.extend_from_slice(plaintext);
+        apply_keystream(&KEY, &nonce, &mut data);
+
+        assert_eq!(&data[64..], &expected[..]);
+
+        apply_keystream(&KEY, &nonce, &mut data);
+        assert_eq!(&data[64..], &plaintext[..]);
+    }
+}
@@ -0,0 +1,105 @@
+use core::ptr;
+use core::ops::{ BitAnd, BitOr, BitXor, Not };
+
+
+/// Constant Time Boolean
+///
+/// Either `0` or `1`, like [`subtle::Choice`](https://docs.rs/subtle/*/subtle/struct.Choice.html).
+/// Convert it to `bool` only when the result is public.
+///
+/// ```
+/// use seckey::{ Choice, CmpKey };
+///
+/// let choice = CmpKey(0u64).is_zero() & !CmpKey(1u64).is_zero();
+/// assert_eq!(1, choice.unwrap_u8());
+/// assert!(bool::from(choice));
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Choice(u8);
+
+impl Choice {
+    #[inline]
+    pub fn unwrap_u8(&self) -> u8 {
+        self.0
+    }
+
+    /// `0xff` if true, `0x00` if false.
+    #[inline]
+    pub(crate) fn mask(self) -> u8 {
+        0u8.wrapping_sub(self.0)
+    }
+}
+
+/// Hide the value from the optimizer, so it can not introduce a branch.
+#[inline(never)]
+pub(crate) fn black_box(input: u8) -> u8 {
+    unsafe { ptr::read_volatile(&input) }
+}
+
+impl From<u8> for Choice {
+    /// `input` must be `0` or `1`.
+    #[inline]
+    fn from(input: u8) -> Choice {
+        debug_assert!(input == 0 || input == 1);
+        Choice(black_box(input))
+    }
+}
+
+impl From<Choice> for bool {
+    #[inline]
+    fn from(choice: Choice) -> bool {
+        choice.0 != 0
+    }
+}
+
+impl BitAnd for Choice {
+    type Output = Choice;
+
+    #[inline]
+    fn bitand(self, rhs: Choice) -> Choice {
+        Choice(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Choice {
+    type Output = Choice;
+
+    #[inline]
+    fn bitor(self, rhs: Choice) -> Choice {
+        Choice(self.0 | rhs.0)
+    }
+}
+
+impl BitXor for Choice {
+    type Output = Choice;
+
+    #[inline]
+    fn bitxor(self, rhs: Choice) -> Choice {
+        Choice(self.0 ^ rhs.0)
+    }
+}
+
+impl Not for Choice {
+    type Output = Choice;
+
+    #[inline]
+    fn not(self) -> Choice {
+        Choice(1 & !self.0)
+    }
+}
+
+#[cfg(feature = "subtle")]
+impl From<::subtle::Choice> for Choice {
+    #[inline]
+    fn from(choice: ::subtle::Choice) -> Choice {
+        Choice::from(choice.unwrap_u8())
+    }
+}
+
+#[cfg(feature = "subtle")]
+impl From<Choice> for ::subtle::Choice {
+    #[inline]
+    fn from(choice: Choice) -> ::subtle::Choice {
+        ::subtle::Choice::from(choice.0)
+    }
+}
@@ -1,13 +1,16 @@
-use core::{ fmt, mem };
+use core::{ fmt, mem, slice };
 use core::cmp::{ self, Ordering };
 use memsec::{ memeq, memcmp };
+use choice::{ Choice, black_box };
+use ctord::CtOrd;
+use ::{ ZeroSafe, NoPadding };
 
 
 /// Constant Time Compare
 ///
 /// # Note
 ///
-/// it compare memory value.
+/// Equality compares memory value, order follows [`CtOrd`](trait.CtOrd.html).
 #[cfg_attr(feature = "nightly", repr(transparent))]
 pub struct CmpKey<T: ?Sized + 'static>(pub T);
 
@@ -16,6 +19,223 @@ impl<T: ?Sized> CmpKey<T> {
     pub fn from(t: &T) -> &CmpKey<T> {
         unsafe { &*(t as *const T as *const CmpKey<T>) }
     }
+
+    pub fn from_mut(t: &mut T) -> &mut CmpKey<T> {
+        unsafe { &mut *(t as *mut T as *mut CmpKey<T>) }
+    }
+}
+
+#[inline]
+pub(crate) fn bytes<T: ?Sized + NoPadding>(t: &T) -> &[u8] {
+    unsafe { slice::from_raw_parts(t as *const T as *const u8, mem::size_of_val(t)) }
+}
+
+#[inline]
+unsafe fn bytes_mut<T: ?Sized + NoPadding>(t: &mut T) -> &mut [u8] {
+    slice::from_raw_parts_mut(t as *mut T as *mut u8, mem::size_of_val(t))
+}
+
+/// `1` if `x == 0`.
+#[inline]
+fn ct_is_zero(x: u8) -> Choice {
+    Choice::from((u16::from(x).wrapping_sub(1) >> 8) as u8 & 1)
+}
+
+/// Constant time equality of the raw memory, always `0` if the lengths differ.
+pub(crate) fn ct_eq<T: ?Sized>(a: &T, b: &T) -> Choice {
+    let len1 = mem::size_of_val(a);
+    let len2 = mem::size_of_val(b);
+
+    let r = unsafe { memeq(
+        a as *const T as *const u8,
+        b as *const T as *const u8,
+        cmp::min(len1, len2)
+    ) };
+    Choice::from((len1 == len2 && r) as u8)
+}
+
+/// `s[i]`, or zero past the end, without a branch on the length.
+#[inline]
+fn padded_byte(s: &[u8], i: usize) -> u8 {
+    let in_bounds = i.ct_lt(&s.len());
+    let idx = i & 0usize.wrapping_sub(usize::from(in_bounds.unwrap_u8()));
+
+    // only an empty slice takes the other branch
+    let byte = s.get(idx).cloned().unwrap_or(0);
+    black_box(byte) & in_bounds.mask()
+}
+
+impl<T: ?Sized> CmpKey<T> {
+    /// Constant time equality, always `0` if the lengths differ.
+    #[inline]
+    pub fn ct_eq(&self, rhs: &T) -> Choice {
+        ct_eq(&self.0, rhs)
+    }
+
+    /// Bytewise order, as `memcmp` on the raw memory, a shorter prefix is less.
+    ///
+    /// This is not the numeric order of integers on little-endian targets, see `CtOrd`.
+    ///
+    /// ```
+    /// use std::cmp::Ordering;
+    /// use seckey::CmpKey;
+    ///
+    /// assert_eq!(CmpKey([1u8, 2]).bytes_cmp(&[1, 3]), Ordering::Less);
+    /// assert_eq!(CmpKey::from(&[2u8][..]).bytes_cmp(&[1, 1][..]), Ordering::Greater);
+    /// ```
+    pub fn bytes_cmp(&self, rhs: &T) -> Ordering {
+        let len1 = mem::size_of_val(&self.0);
+        let len2 = mem::size_of_val(rhs);
+
+        let order = unsafe { memcmp(
+            &self.0 as *const T as *const u8,
+            rhs as *const T as *const u8,
+            cmp::min(len1, len2))
+        };
+
+        let r = len1.cmp(&len2);
+        match order.cmp(&0) {
+            Ordering::Equal => r,
+            order => order
+        }
+    }
+}
+
+impl<T: ?Sized + NoPadding> CmpKey<T> {
+    /// Constant time check that every byte is zero.
+    ///
+    /// ```
+    /// use seckey::CmpKey;
+    ///
+    /// assert!(bool::from(CmpKey([0u8; 4]).is_zero()));
+    /// assert!(!bool::from(CmpKey::from(&[0u8, 0, 1][..]).is_zero()));
+    /// ```
+    pub fn is_zero(&self) -> Choice {
+        let acc = bytes(&self.0).iter().fold(0, |acc, &b| acc | black_box(b));
+        ct_is_zero(acc)
+    }
+
+    /// Length hiding equality.
+    ///
+    /// Compares `max_len` bytes on both sides, reading zero past the end of the shorter one,
+    /// so the running time depends only on the public `max_len`.
+    /// Always `0` if the lengths differ or exceed `max_len`.
+    ///
+    /// ```
+    /// use seckey::CmpKey;
+    ///
+    /// let token = "secret-token";
+    /// assert!(bool::from(CmpKey::from(token).eq_padded("secret-token", 64)));
+    /// assert!(!bool::from(CmpKey::from(token).eq_padded("secret", 64)));
+    /// assert!(!bool::from(CmpKey::from(token).eq_padded("secret-token", 8)));
+    /// ```
+    pub fn eq_padded(&self, rhs: &T, max_len: usize) -> Choice {
+        let a = bytes(&self.0);
+        let b = bytes(rhs);
+
+        let mut diff = 0;
+        for i in 0..max_len {
+            diff |= padded_byte(a, i) ^ padded_byte(b, i);
+        }
+
+        let same_len = CmpKey(a.len()).ct_eq(&b.len());
+        let fits = !max_len.ct_lt(&a.len());
+        ct_is_zero(diff) & same_len & fits
+    }
+}
+
+impl<T: ?Sized + CtOrd> CmpKey<T> {
+    /// Constant time `self < rhs`, see `CtOrd`.
+    ///
+    /// ```
+    /// use seckey::CmpKey;
+    ///
+    /// assert!(bool::from(CmpKey([1u8, 2]).ct_lt(&[1, 3])));
+    /// assert!(!bool::from(CmpKey([1u8, 2]).ct_lt(&[1, 2])));
+    /// assert!(bool::from(CmpKey(-1i32).ct_lt(&0)));
+    /// ```
+    #[inline]
+    pub fn ct_lt(&self, rhs: &T) -> Choice {
+        self.0.ct_lt(rhs)
+    }
+
+    /// Constant time `self > rhs`, see `CtOrd`.
+    #[inline]
+    pub fn ct_gt(&self, rhs: &T) -> Choice {
+        self.0.ct_gt(rhs)
+    }
+}
+
+impl<T: ?Sized + ZeroSafe + NoPadding> CmpKey<T> {
+    /// Constant time `if choice { *self = *rhs }`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ.
+    ///
+    /// ```
+    /// use seckey::{ Choice, CmpKey };
+    ///
+    /// let mut key = CmpKey([1u8; 4]);
+    /// key.conditional_assign(&[2; 4], Choice::from(0));
+    /// assert_eq!(key, [1; 4]);
+    /// key.conditional_assign(&[2; 4], Choice::from(1));
+    /// assert_eq!(key, [2; 4]);
+    /// ```
+    pub fn conditional_assign(&mut self, rhs: &T, choice: Choice) {
+        let mask = choice.mask();
+        let dst = unsafe { bytes_mut(&mut self.0) };
+        let src = bytes(rhs);
+        assert_eq!(dst.len(), src.len(), "length mismatch");
+
+        for (x, &y) in dst.iter_mut().zip(src) {
+            *x ^= mask & (*x ^ y);
+        }
+    }
+
+    /// Constant time `if choice { mem::swap(self, rhs) }`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ.
+    pub fn conditional_swap(&mut self, rhs: &mut T, choice: Choice) {
+        let mask = choice.mask();
+        let a = unsafe { bytes_mut(&mut self.0) };
+        let b = unsafe { bytes_mut(rhs) };
+        assert_eq!(a.len(), b.len(), "length mismatch");
+
+        for (x, y) in a.iter_mut().zip(b.iter_mut()) {
+            let t = mask & (*x ^ *y);
+            *x ^= t;
+            *y ^= t;
+        }
+    }
+}
+
+impl<T: ZeroSafe + NoPadding + Copy> CmpKey<T> {
+    /// Constant time `if choice { b } else { a }`.
+    ///
+    /// ```
+    /// use seckey::{ Choice, CmpKey };
+    ///
+    /// let a = 1u64;
+    /// let b = 2u64;
+    /// assert_eq!(1, CmpKey::ct_select(&a, &b, Choice::from(0)));
+    /// assert_eq!(2, CmpKey::ct_select(&a, &b, Choice::from(1)));
+    /// ```
+    pub fn ct_select(a: &T, b: &T, choice: Choice) -> T {
+        let mut out = CmpKey(*a);
+        out.conditional_assign(b, choice);
+        out.0
+    }
+}
+
+#[cfg(feature = "subtle")]
+impl<T: ?Sized> ::subtle::ConstantTimeEq for CmpKey<T> {
+    #[inline]
+    fn ct_eq(&self, CmpKey(rhs): &CmpKey<T>) -> ::subtle::Choice {
+        CmpKey::ct_eq(self, rhs).into()
+    }
 }
 
 impl<T: ?Sized> fmt::Debug for CmpKey<T> {
@@ -41,40 +261,38 @@ impl<T: ?Sized> PartialEq<T> for CmpKey<T> {
 }
 
 impl<T: ?Sized> PartialEq<CmpKey<T>> for CmpKey<T> {
-    fn eq(&self, &CmpKey(ref rhs): &CmpKey<T>) -> bool {
+    fn eq(&self, CmpKey(rhs): &CmpKey<T>) -> bool {
         self.eq(rhs)
     }
 }
 
 impl<T: ?Sized> Eq for CmpKey<T> {}
 
-impl<T: ?Sized> PartialOrd<T> for CmpKey<T> {
+impl<T: ?Sized + CtOrd> PartialOrd<T> for CmpKey<T> {
     fn partial_cmp(&self, rhs: &T) -> Option<Ordering> {
         self.partial_cmp(CmpKey::from(rhs))
     }
 }
 
-impl<T: ?Sized> PartialOrd<CmpKey<T>> for CmpKey<T> {
+impl<T: ?Sized + CtOrd> PartialOrd<CmpKey<T>> for CmpKey<T> {
     fn partial_cmp(&self, rhs: &CmpKey<T>) -> Option<Ordering> {
         Some(self.cmp(rhs))
     }
 }
 
-impl<T: ?Sized> Ord for CmpKey<T> {
-    fn cmp(&self, &CmpKey(ref rhs): &CmpKey<T>) -> Ordering {
-        let len1 = mem::size_of_val(&self.0);
-        let len2 = mem::size_of_val(rhs);
-
-        let order = unsafe { memcmp(
-            &self.0 as *const T as *const u8,
-            rhs as *const T as *const u8,
-            cmp::min(len1, len2))
-        };
+/// Order by `CtOrd`, eg. numeric order for integers.
+///
+/// Use `bytes_cmp` for the order of the raw memory.
+impl<T: ?Sized + CtOrd> Ord for CmpKey<T> {
+    fn cmp(&self, CmpKey(rhs): &CmpKey<T>) -> Ordering {
+        let lt = self.0.ct_lt(rhs);
+        let gt = self.0.ct_gt(rhs);
 
-        let r = len1.cmp(&len2);
-        match order.cmp(&0) {
-            Ordering::Equal => r,
-            order => order
+        // only the result is revealed
+        match (bool::from(lt), bool::from(gt)) {
+            (true, _) => Ordering::Less,
+            (_, true) => Ordering::Greater,
+            _ => Ordering::Equal
         }
     }
 }
@@ -0,0 +1,98 @@
+use choice::Choice;
+
+
+/// Constant Time Order
+///
+/// Numeric order for integers, independent of the byte order of the target,
+/// and lexicographic order for arrays, slices and `str`.
+///
+/// ```
+/// use seckey::CtOrd;
+///
+/// assert!(bool::from((-1i32).ct_lt(&0)));
+/// assert!(bool::from(256u32.ct_gt(&1)));
+/// assert!(bool::from([1u16, 256].ct_gt(&[1, 2])));
+/// ```
+pub trait CtOrd {
+    /// `1` if `self < rhs`.
+    fn ct_lt(&self, rhs: &Self) -> Choice;
+
+    /// `1` if `self > rhs`.
+    #[inline]
+    fn ct_gt(&self, rhs: &Self) -> Choice {
+        rhs.ct_lt(self)
+    }
+}
+
+macro_rules! impl_ctord {
+    ( Unsigned $( $ty:ty ),* ) => {
+        $(
+            impl CtOrd for $ty {
+                #[inline]
+                fn ct_lt(&self, rhs: &$ty) -> Choice {
+                    const BITS: u32 = <$ty>::BITS;
+
+                    let (a, b) = (*self, *rhs);
+
+                    // the borrow of `a - b`
+                    let borrow = (!a & b) | (!(a ^ b) & a.wrapping_sub(b));
+                    Choice::from((borrow >> (BITS - 1)) as u8)
+                }
+            }
+        )*
+    };
+    ( Signed $( $ty:ty => $uty:ty ),* ) => {
+        $(
+            impl CtOrd for $ty {
+                #[inline]
+                fn ct_lt(&self, rhs: &$ty) -> Choice {
+                    const FLIP: $uty = 1 << (<$uty>::BITS - 1);
+
+                    // flip the sign bit to map onto the unsigned order
+                    ((*self as $uty) ^ FLIP).ct_lt(&((*rhs as $uty) ^ FLIP))
+                }
+            }
+        )*
+    };
+}
+
+impl_ctord!(Unsigned u8, u16, u32, u64, u128, usize);
+impl_ctord!(Signed i8 => u8, i16 => u16, i32 => u32, i64 => u64, i128 => u128, isize => usize);
+
+impl CtOrd for char {
+    #[inline]
+    fn ct_lt(&self, rhs: &char) -> Choice {
+        (*self as u32).ct_lt(&(*rhs as u32))
+    }
+}
+
+impl<T: CtOrd> CtOrd for [T] {
+    /// Lexicographic, a shorter prefix is less. Only the lengths may leak.
+    fn ct_lt(&self, rhs: &[T]) -> Choice {
+        let mut lt = Choice::from(0);
+        let mut gt = Choice::from(0);
+
+        for (a, b) in self.iter().zip(rhs) {
+            let undecided = !(lt | gt);
+            lt = lt | (undecided & a.ct_lt(b));
+            gt = gt | (undecided & a.ct_gt(b));
+        }
+
+        let shorter = Choice::from((self.len() < rhs.len()) as u8);
+        lt | (!(lt | gt) & shorter)
+    }
+}
+
+impl<T: CtOrd, const N: usize> CtOrd for [T; N] {
+    #[inline]
+    fn ct_lt(&self, rhs: &[T; N]) -> Choice {
+        self[..].ct_lt(&rhs[..])
+    }
+}
+
+impl CtOrd for str {
+    #[inline]
+    fn ct_lt(&self, rhs: &str) -> Choice {
+        self.as_bytes().ct_lt(rhs.as_bytes())
+    }
+}
@@ -0,0 +1,243 @@
+//! Constant-time hex and base64.
+//!
+//! Every byte goes through the same arithmetic, with no table lookups
+//! and no branches on secret data. Only the input length may leak.
+
+use core::str;
+use memsec::memzero;
+use seckey::alloc_sized;
+use ::{ SecKey, SecKeyError, SecReadGuard, TempKey };
+
+
+/// `0xff` if `lo <= c <= hi`, else `0`.
+#[inline]
+fn ct_range(c: u8, lo: u8, hi: u8) -> u8 {
+    let c = i16::from(c);
+    (((i16::from(lo) - 1 - c) & (c - i16::from(hi) - 1)) >> 8) as u8
+}
+
+/// `0xff` if `a > b`, else `0`.
+#[inline]
+fn ct_gt(a: u8, b: u8) -> u8 {
+    ((i16::from(b) - i16::from(a)) >> 8) as u8
+}
+
+/// Decode a hex char, `invalid` is set to `0xff` if it is not one.
+#[inline]
+fn decode_nibble(c: u8, invalid: &mut u8) -> u8 {
+    let digit = ct_range(c, b'0', b'9');
+    let lower = ct_range(c, b'a', b'f');
+    let upper = ct_range(c, b'A', b'F');
+
+    *invalid |= !(digit | lower | upper);
+
+    (digit & c.wrapping_sub(b'0'))
+        | (lower & c.wrapping_sub(b'a' - 10))
+        | (upper & c.wrapping_sub(b'A' - 10))
+}
+
+#[inline]
+fn encode_nibble(n: u8) -> u8 {
+    n + b'0' + (ct_gt(n, 9) & (b'a' - b'0' - 10))
+}
+
+/// Decode a base64 char, `invalid` is set to `0xff` if it is not one.
+#[inline]
+fn decode_sextet(c: u8, invalid: &mut u8) -> u8 {
+    let upper = ct_range(c, b'A', b'Z');
+    let lower = ct_range(c, b'a', b'z');
+    let digit = ct_range(c, b'0', b'9');
+    let plus = ct_range(c, b'+', b'+');
+    let slash = ct_range(c, b'/', b'/');
+
+    *invalid |= !(upper | lower | digit | plus | slash);
+
+    (upper & c.wrapping_sub(b'A'))
+        | (lower & c.wrapping_sub(b'a' - 26))
+        | (digit & c.wrapping_add(52 - b'0'))
+        | (plus & 62)
+        | (slash & 63)
+}
+
+#[inline]
+fn encode_sextet(s: u8) -> u8 {
+    let mut diff = i16::from(b'A');
+    diff += i16::from(ct_gt(s, 25) & 6);
+    diff -= i16::from(ct_gt(s, 51) & 75);
+    diff -= i16::from(ct_gt(s, 61) & 15);
+    diff += i16::from(ct_gt(s, 62) & 3);
+    (i16::from(s) + diff) as u8
+}
+
+/// Decode `len` bytes into a new key, `f` returns `0` if the input is valid.
+///
+/// On failure the partial output is zeroed and freed.
+#[track_caller]
+fn decode_with<F>(src: &mut str, len: usize, f: F) -> Result<SecKey<[u8]>, SecKeyError>
+    where F: FnOnce(&[u8], &mut [u8]) -> u8
+{
+    unsafe {
+        let mut memptr = alloc_sized(len)?;
+        let invalid = f(src.as_bytes(), memptr.as_mut());
+
+        // protect secret
+        let key = SecKey::from_unprotected(memptr)?;
+
+        if invalid != 0 {
+            return Err(SecKeyError::InvalidEncoding);
+        }
+
+        // zero original source
+        let src = src.as_bytes_mut();
+        memzero(src.as_mut_ptr(), src.len());
+
+        Ok(key)
+    }
+}
+
+impl SecKey<[u8]> {
+    /// Decode hex, upper or lower case, in constant time.
+    ///
+    /// On success `src` is zeroed and a new protected `SecKey<[u8]>` is returned.
+    /// On failure `src` remains untouched and the error is returned.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let mut unprotected = "00ff7F".to_string();
+    /// let k = SecKey::from_hex(&mut unprotected).unwrap();
+    ///
+    /// assert_eq!(&unprotected, "\0\0\0\0\0\0");
+    /// assert_eq!(*k.read(), [0x00, 0xff, 0x7f]);
+    /// ```
+    #[track_caller]
+    pub fn from_hex(src: &mut str) -> Result<SecKey<[u8]>, SecKeyError> {
+        if !src.len().is_multiple_of(2) {
+            return Err(SecKeyError::InvalidEncoding);
+        }
+
+        let len = src.len() / 2;
+        decode_with(src, len, |src, dst| {
+            let mut invalid = 0;
+            for (pair, byte) in src.chunks(2).zip(dst.iter_mut()) {
+                *byte = decode_nibble(pair[0], &mut invalid) << 4
+                    | decode_nibble(pair[1], &mut invalid);
+            }
+            invalid
+        })
+    }
+
+    /// Decode padded standard base64 in constant time.
+    ///
+    /// On success `src` is zeroed and a new protected `SecKey<[u8]>` is returned.
+    /// On failure `src` remains untouched and the error is returned.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let mut unprotected = "Zm9vYmE=".to_string();
+    /// let k = SecKey::from_base64(&mut unprotected).unwrap();
+    ///
+    /// assert_eq!(&unprotected, "\0\0\0\0\0\0\0\0");
+    /// assert_eq!(*k.read(), *b"fooba");
+    /// ```
+    #[track_caller]
+    pub fn from_base64(src: &mut str) -> Result<SecKey<[u8]>, SecKeyError> {
+        // the padding only depends on the length, which is public
+        let bytes = src.as_bytes();
+        if !bytes.len().is_multiple_of(4) {
+            return Err(SecKeyError::InvalidEncoding);
+        }
+        let pad = bytes.iter().rev().take(2).take_while(|&&c| c == b'=').count();
+        let chars = bytes.len() - pad;
+        let len = chars * 6 / 8;
+
+        decode_with(src, len, |src, dst| {
+            let mut invalid = 0;
+            let mut acc = 0u32;
+            let mut bits = 0;
+            let mut out = dst.iter_mut();
+
+            for &c in &src[..chars] {
+                acc = acc << 6 | u32::from(decode_sextet(c, &mut invalid));
+                bits += 6;
+                if bits >= 8 {
+                    bits -= 8;
+                    if let Some(byte) = out.next() {
+                        *byte = (acc >> bits) as u8;
+                    }
+                }
+            }
+
+            // reject non-canonical trailing bits
+            invalid |= ct_gt((acc & ((1 << bits) - 1)) as u8, 0);
+            invalid
+        })
+    }
+}
+
+impl<'a, T: 'a + ?Sized + AsRef<[u8]>> SecReadGuard<'a, T> {
+    /// Encode as lower case hex into `dst`, in constant time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is shorter than twice the key length.
+    ///
+    /// ```
+    /// use seckey::{ SecKey, TempKey };
+    ///
+    /// let k = SecKey::new([0x00u8, 0xff, 0x7f]).unwrap();
+    /// let mut buf = [0u8; 6];
+    /// let mut buf = TempKey::from(&mut buf[..]);
+    /// assert_eq!(k.read().encode_hex(&mut buf), "00ff7f");
+    /// ```
+    pub fn encode_hex<'t>(&self, dst: &'t mut TempKey<'_, [u8]>) -> &'t str {
+        let src = (**self).as_ref();
+        let dst = &mut dst[..src.len() * 2];
+
+        for (byte, pair) in src.iter().zip(dst.chunks_mut(2)) {
+            pair[0] = encode_nibble(byte >> 4);
+            pair[1] = encode_nibble(byte & 0xf);
+        }
+
+        unsafe { str::from_utf8_unchecked(dst) }
+    }
+
+    /// Encode as padded standard base64 into `dst`, in constant time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `dst` is shorter than `(len + 2) / 3 * 4`.
+    ///
+    /// ```
+    /// use seckey::{ SecKey, TempKey };
+    ///
+    /// let mut unprotected = *b"fooba";
+    /// let k = SecKey::from_bytes(&mut unprotected[..]).unwrap();
+    /// let mut buf = [0u8; 8];
+    /// let mut buf = TempKey::from(&mut buf[..]);
+    /// assert_eq!(k.read().encode_base64(&mut buf), "Zm9vYmE=");
+    /// ```
+    pub fn encode_base64<'t>(&self, dst: &'t mut TempKey<'_, [u8]>) -> &'t str {
+        let src = (**self).as_ref();
+        let dst = &mut dst[..src.len().div_ceil(3) * 4];
+
+        for (chunk, quad) in src.chunks(3).zip(dst.chunks_mut(4)) {
+            let mut block = [0; 3];
+            block[..chunk.len()].copy_from_slice(chunk);
+            let acc = u32::from(block[0]) << 16 | u32::from(block[1]) << 8 | u32::from(block[2]);
+
+            for (i, c) in quad.iter_mut().enumerate() {
+                *c = if i <= chunk.len() {
+                    encode_sextet((acc >> (18 - 6 * i)) as u8 & 0x3f)
+                } else {
+                    b'='
+                };
+            }
+
+            ::zero(&mut block);
+        }
+
+        unsafe { str::from_utf8_unchecked(dst) }
+    }
+}
@@ -0,0 +1,74 @@
+use core::fmt;
+use std::{ error, io };
+
+
+/// Secure Key Error
+///
+/// Carries the OS error code where the failing call sets one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SecKeyError {
+    /// `memsec::malloc` failed, eg. out of memory or an overflowing size.
+    AllocFailed,
+    /// `mlock` failed, usually `RLIMIT_MEMLOCK` is exhausted.
+    LockFailed(Option<i32>),
+    /// `mprotect` failed.
+    ProtectFailed(Option<i32>),
+    /// `madvise` failed, eg. the kernel does not know `MADV_WIPEONFORK`.
+    AdviseFailed(Option<i32>),
+    /// The OS random number generator failed.
+    RandomFailed(Option<i32>),
+    /// The input is not valid hex or base64.
+    InvalidEncoding
+}
+
+impl SecKeyError {
+    #[inline]
+    pub(crate) fn last_errno() -> Option<i32> {
+        io::Error::last_os_error().raw_os_error()
+    }
+
+    /// The OS error code, if there is one.
+    pub fn errno(&self) -> Option<i32> {
+        match *self {
+            SecKeyError::AllocFailed | SecKeyError::InvalidEncoding => None,
+            SecKeyError::LockFailed(errno)
+                | SecKeyError::ProtectFailed(errno)
+                | SecKeyError::AdviseFailed(errno)
+                | SecKeyError::RandomFailed(errno) => errno
+        }
+    }
+}
+
+impl fmt::Display for SecKeyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match *self {
+            SecKeyError::AllocFailed => "secure allocation failed",
+            SecKeyError::LockFailed(_) => "failed to lock secure memory",
+            SecKeyError::ProtectFailed(_) => "failed to protect secure memory",
+            SecKeyError::AdviseFailed(_) => "failed to advise secure memory",
+            SecKeyError::RandomFailed(_) => "failed to generate random bytes",
+            SecKeyError::InvalidEncoding => "invalid encoding"
+        };
+
+        match self.errno() {
+            Some(errno) => write!(f, "{}: {}", msg, io::Error::from_raw_os_error(errno)),
+            None => f.write_str(msg)
+        }
+    }
+}
+
+impl error::Error for SecKeyError {}
+
+impl From<SecKeyError> for io::Error {
+    fn from(err: SecKeyError) -> io::Error {
+        let kind = match err {
+            SecKeyError::AllocFailed => io::ErrorKind::OutOfMemory,
+            SecKeyError::InvalidEncoding => io::ErrorKind::InvalidData,
+            _ => err.errno()
+                .map(|errno| io::Error::from_raw_os_error(errno).kind())
+                .unwrap_or(io::ErrorKind::Other)
+        };
+
+        io::Error::new(kind, err)
+    }
+}
@@ -0,0 +1,126 @@
+//! `pthread_atfork` fallback for `MADV_WIPEONFORK`.
+//!
+//! Where the kernel can not wipe a key in the child, it is registered here,
+//! and the child handler zeroes it, canary included, the same as the kernel would.
+//! Registered keys are protected through `mprotect` here, so the child
+//! handler puts back the protection the key had at `fork`.
+
+use core::hint;
+use core::ptr::NonNull;
+use core::cell::UnsafeCell;
+use core::sync::atomic::{ AtomicBool, Ordering };
+use std::collections::BTreeMap;
+use std::sync::Once;
+use libc;
+use memsec::{ self, memzero, Prot };
+
+
+const CANARY_SIZE: usize = 16;
+
+struct Region {
+    len: usize,
+    prot: Prot::Ty
+}
+
+/// A spin lock, the fork handlers can not hold a `MutexGuard`.
+struct Registry {
+    locked: AtomicBool,
+    used: AtomicBool,
+    /// Keyed by the address of the value, `BTreeMap::new` is `const`.
+    regions: UnsafeCell<BTreeMap<usize, Region>>
+}
+
+unsafe impl Sync for Registry {}
+
+static REGISTRY: Registry = Registry {
+    locked: AtomicBool::new(false),
+    used: AtomicBool::new(false),
+    regions: UnsafeCell::new(BTreeMap::new())
+};
+
+static INSTALL: Once = Once::new();
+
+fn acquire() {
+    while REGISTRY.locked.compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed).is_err() {
+        hint::spin_loop();
+    }
+}
+
+fn release() {
+    REGISTRY.locked.store(false, Ordering::Release);
+}
+
+/// Run `f` with the regions, holding the lock.
+fn with_regions<F, R>(f: F) -> R
+    where F: FnOnce(&mut BTreeMap<usize, Region>) -> R
+{
+    acquire();
+    let output = f(unsafe { &mut *REGISTRY.regions.get() });
+    release();
+    output
+}
+
+#[inline]
+fn addr<T: ?Sized>(ptr: NonNull<T>) -> usize {
+    ptr.cast::<u8>().as_ptr() as usize
+}
+
+extern "C" fn prepare() {
+    acquire();
+}
+
+extern "C" fn parent() {
+    release();
+}
+
+extern "C" fn child() {
+    // only the forking thread survives, and it holds the lock
+    unsafe {
+        for (&addr, region) in &*REGISTRY.regions.get() {
+            let ptr = NonNull::slice_from_raw_parts(NonNull::new_unchecked(addr as *mut u8), region.len);
+            memsec::mprotect(ptr, Prot::ReadWrite);
+            memzero((addr - CANARY_SIZE) as *mut u8, CANARY_SIZE + region.len);
+            memsec::mprotect(ptr, region.prot);
+        }
+    }
+
+    release();
+}
+
+/// Zero `size` bytes at `memptr`, and the canary in front, in every child process.
+///
+/// The memory must be a fresh, writable allocation.
+pub(crate) fn register(memptr: NonNull<u8>, size: usize) {
+    INSTALL.call_once(|| unsafe {
+        libc::pthread_atfork(Some(prepare), Some(parent), Some(child));
+    });
+
+    REGISTRY.used.store(true, Ordering::Release);
+    with_regions(|regions| regions.insert(addr(memptr), Region { len: size, prot: Prot::ReadWrite }));
+}
+
+/// Forget a key before it is freed.
+pub(crate) fn unregister<T: ?Sized>(ptr: NonNull<T>) {
+    if !REGISTRY.used.load(Ordering::Acquire) {
+        return;
+    }
+
+    with_regions(|regions| regions.remove(&addr(ptr)));
+}
+
+/// `mprotect` a key, and remember the protection if it is registered.
+///
+/// Both happen under the lock, so a `fork` sees the protection the key has.
+pub(crate) unsafe fn mprotect<T: ?Sized>(ptr: NonNull<T>, prot: Prot::Ty) -> bool {
+    if !REGISTRY.used.load(Ordering::Acquire) {
+        return memsec::mprotect(ptr, prot);
+    }
+
+    with_regions(|regions| {
+        let ok = memsec::mprotect(ptr, prot);
+        if let (true, Some(region)) = (ok, regions.get_mut(&addr(ptr))) {
+            region.prot = prot;
+        }
+        ok
+    })
+}
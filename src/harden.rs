@@ -0,0 +1,103 @@
+//! Process-wide hardening.
+//!
+//! The secure heap only covers the keys themselves, copies on the stack
+//! or in plain buffers still end up in core dumps, and a debugger
+//! can read the whole address space. These steps close both.
+
+use core::fmt;
+use std::io;
+#[cfg(unix)] use core::mem;
+#[cfg(unix)] use libc;
+
+
+/// Steps taken by [`harden_process`](fn.harden_process.html).
+#[derive(Debug)]
+pub struct Hardening {
+    /// `prctl(PR_SET_DUMPABLE, 0)`, Linux only.
+    ///
+    /// Other systems report `Unsupported`.
+    pub not_dumpable: io::Result<()>,
+    /// `setrlimit(RLIMIT_CORE, 0)`, for the soft and the hard limit.
+    pub no_core: io::Result<()>
+}
+
+impl Hardening {
+    /// Every step succeeded.
+    ///
+    /// Always `false` outside of Linux, where `not_dumpable` is unsupported.
+    #[inline]
+    pub fn is_complete(&self) -> bool {
+        self.not_dumpable.is_ok() && self.no_core.is_ok()
+    }
+}
+
+impl fmt::Display for Hardening {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn step(f: &mut fmt::Formatter, name: &str, result: &io::Result<()>) -> fmt::Result {
+            match *result {
+                Ok(()) => writeln!(f, "{}: ok", name),
+                Err(ref err) => writeln!(f, "{}: {}", name, err)
+            }
+        }
+
+        step(f, "PR_SET_DUMPABLE=0", &self.not_dumpable)?;
+        step(f, "RLIMIT_CORE=0", &self.no_core)
+    }
+}
+
+/// Keep key material out of core dumps and debuggers, for the whole process.
+///
+/// Keys are already excluded from core dumps with `MADV_DONTDUMP`,
+/// this also covers copies outside of the secure heap.
+/// A non-dumpable process can not be attached with `ptrace` by other users,
+/// and its `/proc/<pid>` files are owned by root.
+/// Only Linux has `PR_SET_DUMPABLE`, elsewhere that step reports `Unsupported`.
+///
+/// The steps are independent, each one is tried and reported.
+///
+/// ```
+/// let hardening = seckey::harden_process();
+/// if !hardening.is_complete() {
+///     eprint!("{}", hardening);
+/// }
+/// ```
+pub fn harden_process() -> Hardening {
+    Hardening {
+        not_dumpable: set_not_dumpable(),
+        no_core: set_no_core()
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn set_not_dumpable() -> io::Result<()> {
+    if unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+fn set_not_dumpable() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "PR_SET_DUMPABLE is only supported on Linux"))
+}
+
+#[cfg(unix)]
+fn set_no_core() -> io::Result<()> {
+    unsafe {
+        let mut rlim: libc::rlimit = mem::zeroed();
+        rlim.rlim_cur = 0;
+        rlim.rlim_max = 0;
+
+        if libc::setrlimit(libc::RLIMIT_CORE, &rlim) == 0 {
+            Ok(())
+        } else {
+            Err(io::Error::last_os_error())
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn set_no_core() -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "RLIMIT_CORE is only supported on Unix"))
+}
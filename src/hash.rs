@@ -0,0 +1,49 @@
+use core::hash::{ Hash, Hasher };
+use std::sync::OnceLock;
+use siphasher::sip::SipHasher24;
+use ::{ SecKey, SyncSecKey, NoPadding, Policy };
+
+
+/// Per-process SipHash key, never leaves the secure heap.
+///
+/// It is not zeroed in child processes, so inherited keys hash the same.
+static HASH_KEY: OnceLock<SyncSecKey<[u8; 16]>> = OnceLock::new();
+
+fn hash_key() -> &'static SyncSecKey<[u8; 16]> {
+    HASH_KEY.get_or_init(|| {
+        let key = SecKey::<[u8; 16]>::random_with_policy(Policy::new().wipe_on_fork(false))
+            .unwrap_or_else(|err| panic!("{}", err));
+        SyncSecKey::from(key)
+    })
+}
+
+/// Keyed hash of the raw memory, for types without padding.
+///
+/// The bytes are hashed with SipHash-2-4 under a per-process random key,
+/// and only the 64-bit tag is fed to `state`,
+/// so a `HashMap` never sees the secret itself.
+///
+/// # Panics
+///
+/// Panics if the per-process key can not be created.
+///
+/// ```
+/// use std::collections::HashSet;
+/// use seckey::SecKey;
+///
+/// let mut set = HashSet::new();
+/// set.insert(SecKey::new([1u8; 32]).unwrap());
+/// assert!(set.contains(&SecKey::new([1u8; 32]).unwrap()));
+/// assert!(!set.contains(&SecKey::new([2u8; 32]).unwrap()));
+/// ```
+impl<T: ?Sized + NoPadding> Hash for SecKey<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let tag = {
+            let mut hasher = SipHasher24::new_with_key(&hash_key().read());
+            self.with_read(|t| hasher.write(::cmpkey::bytes(t)));
+            hasher.finish()
+        };
+
+        state.write_u64(tag);
+    }
+}
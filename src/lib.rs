@@ -4,18 +4,60 @@
 
 #[cfg(feature = "use_std")] extern crate std;
 extern crate memsec;
+#[cfg(feature = "use_std")] extern crate getrandom;
+#[cfg(feature = "use_std")] extern crate siphasher;
+#[cfg(feature = "derive")] extern crate seckey_derive;
+#[cfg(all(feature = "serde", feature = "use_std"))] extern crate serde;
+#[cfg(feature = "libc")] extern crate libc;
+#[cfg(feature = "rand_core")] extern crate rand_core;
+#[cfg(feature = "subtle")] extern crate subtle;
 
+mod choice;
 mod cmpkey;
+mod ctord;
 mod tempkey;
 mod zerosafe;
+mod nopadding;
+#[cfg(feature = "use_std")] mod error;
 #[cfg(feature = "use_std")] mod seckey;
+#[cfg(feature = "use_std")] mod canary;
+#[cfg(feature = "use_std")] mod policy;
+#[cfg(all(feature = "use_std", unix))] mod fork;
+#[cfg(feature = "use_std")] mod harden;
+#[cfg(feature = "use_std")] mod synckey;
+#[cfg(feature = "use_std")] mod secvec;
+#[cfg(feature = "use_std")] mod secstring;
+#[cfg(feature = "use_std")] mod chacha20;
+#[cfg(feature = "use_std")] mod sealed;
+#[cfg(feature = "use_std")] mod pool;
+#[cfg(feature = "use_std")] mod encoding;
+#[cfg(feature = "use_std")] mod reader;
+#[cfg(feature = "use_std")] mod random;
+#[cfg(feature = "use_std")] mod hash;
+#[cfg(all(feature = "tty", unix))] pub mod prompt;
+#[cfg(feature = "stats")] pub mod stats;
+#[cfg(all(feature = "serde", feature = "use_std"))] mod serialize;
 
 use core::{ mem, ptr };
 
 pub use zerosafe::{ ZeroSafe, zero, unsafe_zero };
+pub use nopadding::NoPadding;
+#[cfg(feature = "derive")] pub use seckey_derive::ZeroSafe;
+pub use choice::Choice;
 pub use cmpkey::CmpKey;
+pub use ctord::CtOrd;
 pub use tempkey::*;
+#[cfg(feature = "use_std")] pub use error::SecKeyError;
 #[cfg(feature = "use_std")] pub use seckey::*;
+#[cfg(feature = "use_std")] pub use policy::{ Policy, Idle };
+#[cfg(feature = "use_std")] pub use synckey::*;
+#[cfg(feature = "use_std")] pub use secvec::SecVec;
+#[cfg(feature = "use_std")] pub use secstring::SecString;
+#[cfg(feature = "use_std")] pub use sealed::SealedKey;
+#[cfg(feature = "use_std")] pub use pool::{ SecPool, PooledKey };
+#[cfg(feature = "use_std")] pub use reader::SecretFile;
+#[cfg(feature = "use_std")] pub use harden::{ harden_process, Hardening };
+#[cfg(all(feature = "serde", feature = "use_std"))] pub use serialize::Exposed;
 
 
 /// Free a value
@@ -0,0 +1,43 @@
+use core::num::Wrapping;
+
+
+/// Types without padding, every byte of a value is initialized.
+///
+/// Required to view a value as `&[u8]`, eg. to hash, compare or mask it.
+///
+/// ```compile_fail
+/// use seckey::CmpKey;
+///
+/// // three padding bytes after the `u8`
+/// CmpKey((1u8, 2u32)).is_zero();
+/// ```
+///
+/// # Safety
+///
+/// Implementors must not contain padding bytes, or fields that do.
+pub unsafe trait NoPadding {}
+
+macro_rules! impl_nopadding {
+    ( Type : $( $t:ty ),* ) => {
+        $(
+            unsafe impl NoPadding for $t {}
+        )*
+    };
+    ( Generic : $( $t:ty ),* ) => {
+        $(
+            unsafe impl<T: NoPadding> NoPadding for $t {}
+        )*
+    }
+}
+
+impl_nopadding!{ Type:
+    usize, u8, u16, u32, u64, u128,
+    isize, i8, i16, i32, i64, i128,
+    f32, f64,
+
+    bool, char, str
+}
+
+impl_nopadding!{ Generic: [T], Wrapping<T> }
+
+unsafe impl<T: NoPadding, const N: usize> NoPadding for [T; N] {}
@@ -0,0 +1,269 @@
+use core::ptr::NonNull;
+use memsec::{ mlock, munlock, Prot };
+#[cfg(not(unix))] use memsec::mprotect;
+#[cfg(unix)] use std::sync::OnceLock;
+#[cfg(unix)] use libc;
+#[cfg(unix)] use fork;
+use ::{ SecKey, SecKeyError };
+
+
+/// Protection Policy
+///
+/// Chooses how a `SecKey` protects its memory. The default is the
+/// strictest: no access while idle, locked into RAM, excluded from core dumps,
+/// and zeroed in child processes.
+///
+/// A hot-path key can stay readable while idle, so `read` costs no `mprotect`.
+///
+/// ```
+/// use seckey::{ Policy, Idle };
+///
+/// let k = Policy::new()
+///     .idle(Idle::ReadOnly)
+///     .lock_memory(true)
+///     .dump_exclude(true)
+///     .build([8u8; 8])
+///     .unwrap();
+/// assert_eq!([8u8; 8], *k.read());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    pub(crate) idle: Prot::Ty,
+    pub(crate) lock_memory: bool,
+    dump_exclude: bool,
+    pub(crate) wipe_on_fork: bool
+}
+
+/// Protection of an idle key, see [`Policy::idle`](struct.Policy.html#method.idle).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Idle {
+    /// Neither readable nor writable.
+    NoAccess,
+    /// Readable, but not writable.
+    ReadOnly
+}
+
+impl Default for Policy {
+    fn default() -> Policy {
+        Policy {
+            idle: Prot::NoAccess,
+            lock_memory: true,
+            dump_exclude: true,
+            wipe_on_fork: true
+        }
+    }
+}
+
+impl Policy {
+    #[inline]
+    pub fn new() -> Policy {
+        Policy::default()
+    }
+
+    /// Protection while no guard is alive, `Idle::NoAccess` by default.
+    ///
+    /// `Idle::ReadOnly` skips both `mprotect` calls of `read`.
+    #[inline]
+    pub fn idle(&mut self, idle: Idle) -> &mut Policy {
+        self.idle = match idle {
+            Idle::NoAccess => Prot::NoAccess,
+            Idle::ReadOnly => Prot::ReadOnly
+        };
+        self
+    }
+
+    /// `mlock` the memory, so it is never swapped out. On by default.
+    #[inline]
+    pub fn lock_memory(&mut self, lock: bool) -> &mut Policy {
+        self.lock_memory = lock;
+        self
+    }
+
+    /// Apply `MADV_DONTDUMP`, so the memory is left out of core dumps. On by default.
+    ///
+    /// Only Linux supports it, elsewhere it is ignored.
+    #[inline]
+    pub fn dump_exclude(&mut self, exclude: bool) -> &mut Policy {
+        self.dump_exclude = exclude;
+        self
+    }
+
+    /// Zero the memory in child processes, canary included. On by default.
+    ///
+    /// Uses `MADV_WIPEONFORK` on Linux 4.14 and later, and a `pthread_atfork`
+    /// handler on other Unix systems. A zeroed value may not be valid,
+    /// so in the child the key can only be dropped, without running its destructor.
+    /// `read` and `write` abort, and guards taken before `fork` must not be used.
+    ///
+    /// Turn it off for keys a pre-fork worker should inherit.
+    #[inline]
+    pub fn wipe_on_fork(&mut self, wipe: bool) -> &mut Policy {
+        self.wipe_on_fork = wipe;
+        self
+    }
+
+    /// Same as `SecKey::new`, with this policy.
+    #[track_caller]
+    #[inline]
+    pub fn build<T>(&self, t: T) -> Result<SecKey<T>, T> {
+        self.try_build(t).map_err(|(t, _)| t)
+    }
+
+    /// Same as `SecKey::try_new`, with this policy.
+    #[track_caller]
+    #[inline]
+    pub fn try_build<T>(&self, t: T) -> Result<SecKey<T>, (T, SecKeyError)> {
+        SecKey::new_with_policy(self, t)
+    }
+
+    /// Same as `SecKey::with`, with this policy.
+    ///
+    /// # Safety
+    ///
+    /// `f` receives uninitialized memory and must fully initialize it.
+    #[track_caller]
+    pub unsafe fn build_with<T, F>(&self, f: F) -> Result<SecKey<T>, SecKeyError>
+        where F: FnOnce(*mut T)
+    {
+        SecKey::with_policy(self, f)
+    }
+
+    /// Same as `SecKey::from_bytes`, with this policy.
+    #[track_caller]
+    pub fn build_from_bytes(&self, src: &mut [u8]) -> Result<SecKey<[u8]>, SecKeyError> {
+        SecKey::from_bytes_with_policy(self, src)
+    }
+
+    /// Lock and advise a fresh `memsec` allocation, before anything is written to it.
+    pub(crate) unsafe fn apply(&self, memptr: NonNull<u8>, size: usize) -> Result<(), SecKeyError> {
+        if self.lock_memory {
+            // `memsec` ignores a failed `mlock`, so lock again to get the error
+            if !mlock(memptr.as_ptr(), size) {
+                return Err(SecKeyError::LockFailed(SecKeyError::last_errno()));
+            }
+        } else {
+            munlock(memptr.as_ptr(), size);
+        }
+
+        #[cfg(any(target_os = "linux", target_os = "android"))]
+        {
+            let dump = if self.dump_exclude { libc::MADV_DONTDUMP } else { libc::MADV_DODUMP };
+            advise(memptr, size, dump)?;
+
+        }
+
+        keep_on_fork(memptr, size)?;
+        if self.wipe_on_fork {
+            wipe_on_fork(memptr, size)?;
+        }
+
+        Ok(())
+    }
+
+    /// Undo `apply` before the memory goes back to the global allocator,
+    /// a reused page would still be zeroed in every child process.
+    pub(crate) unsafe fn release(&self, memptr: NonNull<u8>, size: usize) {
+        if self.wipe_on_fork {
+            #[cfg(unix)]
+            fork::unregister(memptr);
+            let _ = keep_on_fork(memptr, size);
+        }
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const CANARY_SIZE: usize = 16;
+
+#[cfg(unix)]
+pub(crate) fn page_size() -> usize {
+    static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+    *PAGE_SIZE.get_or_init(|| unsafe { libc::sysconf(libc::_SC_PAGESIZE) as usize })
+}
+
+#[cfg(not(unix))]
+pub(crate) fn page_size() -> usize {
+    4096
+}
+
+/// `madvise` the pages `memsec` unprotects for a value, the canary in front of it included.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+unsafe fn advise(memptr: NonNull<u8>, size: usize, advice: libc::c_int) -> Result<(), SecKeyError> {
+    let page_mask = page_size() - 1;
+    let start = (memptr.as_ptr() as usize - CANARY_SIZE) & !page_mask;
+    let end = (memptr.as_ptr() as usize + size + page_mask) & !page_mask;
+
+    if libc::madvise(start as *mut libc::c_void, end - start, advice) == 0 {
+        Ok(())
+    } else {
+        Err(SecKeyError::AdviseFailed(SecKeyError::last_errno()))
+    }
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+unsafe fn wipe_on_fork(memptr: NonNull<u8>, size: usize) -> Result<(), SecKeyError> {
+    match advise(memptr, size, libc::MADV_WIPEONFORK) {
+        // before Linux 4.14
+        Err(SecKeyError::AdviseFailed(Some(libc::EINVAL))) => {
+            fork::register(memptr, size);
+            Ok(())
+        },
+        result => result
+    }
+}
+
+/// Undo `MADV_WIPEONFORK` on the whole `memsec` mapping, guard pages included.
+///
+/// `memsec` pages come from the global allocator, and a reused page keeps its advice.
+/// The size `memsec` stores two pages in front of the value must survive a fork.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+unsafe fn keep_on_fork(memptr: NonNull<u8>, size: usize) -> Result<(), SecKeyError> {
+    let page_size = page_size();
+    let page_mask = page_size - 1;
+    let start = ((memptr.as_ptr() as usize - CANARY_SIZE) & !page_mask) - 2 * page_size;
+    let end = ((memptr.as_ptr() as usize + size + page_mask) & !page_mask) + page_size;
+
+    if libc::madvise(start as *mut libc::c_void, end - start, libc::MADV_KEEPONFORK) == 0 {
+        Ok(())
+    } else {
+        match SecKeyError::last_errno() {
+            // before Linux 4.14, nothing to undo
+            Some(libc::EINVAL) => Ok(()),
+            errno => Err(SecKeyError::AdviseFailed(errno))
+        }
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+unsafe fn wipe_on_fork(memptr: NonNull<u8>, size: usize) -> Result<(), SecKeyError> {
+    fork::register(memptr, size);
+    Ok(())
+}
+
+#[cfg(not(unix))]
+unsafe fn wipe_on_fork(_memptr: NonNull<u8>, _size: usize) -> Result<(), SecKeyError> {
+    Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+unsafe fn keep_on_fork(_memptr: NonNull<u8>, _size: usize) -> Result<(), SecKeyError> {
+    Ok(())
+}
+
+/// `mprotect` a key, the `pthread_atfork` fallback keeps track of it.
+#[cfg(unix)]
+#[inline]
+pub(crate) unsafe fn protect<T: ?Sized>(ptr: NonNull<T>, prot: Prot::Ty) -> bool {
+    fork::mprotect(ptr, prot)
+}
+
+#[cfg(not(unix))]
+#[inline]
+pub(crate) unsafe fn protect<T: ?Sized>(ptr: NonNull<T>, prot: Prot::Ty) -> bool {
+    mprotect(ptr, prot)
+}
+
+/// `read` and `write` need no `mprotect` if the memory is already `have`.
+#[inline]
+pub(crate) fn covers(have: Prot::Ty, want: Prot::Ty) -> bool {
+    have == want || have == Prot::ReadWrite
+}
@@ -0,0 +1,343 @@
+use core::{ any, fmt, mem };
+use core::ptr::{ self, NonNull };
+use core::cell::{ Cell, RefCell };
+use std::{ eprintln, process };
+use std::boxed::Box;
+use std::vec::Vec;
+use getrandom::getrandom;
+use memsec::{ memzero, Prot };
+use seckey::{ Lock, alloc_sized };
+use policy::{ page_size, protect };
+use ::{ SecKey, SecKeyError, SecReadGuard, SecWriteGuard };
+
+
+const CANARY_SIZE: usize = 16;
+
+/// Slots are aligned to the canary size.
+const SLOT_ALIGN: usize = 16;
+
+/// A chunk fills the page `memsec` puts in front of its guard page.
+#[inline]
+fn chunk_len() -> usize {
+    page_size() - CANARY_SIZE
+}
+
+
+/// A shared `memsec` allocation, cut into slots of `[canary][value]`.
+///
+/// All slots share the protection of the chunk, so it stays unlocked
+/// while any of its keys has a guard, and writable while any has a write guard.
+struct Chunk {
+    key: SecKey<[u8]>,
+    count: Cell<usize>,
+    writers: Cell<usize>,
+    prot: Cell<Prot::Ty>,
+    canary: [u8; CANARY_SIZE],
+    stride: usize
+}
+
+impl Chunk {
+    fn new(stride: usize, canary: [u8; CANARY_SIZE]) -> Result<Chunk, SecKeyError> {
+        let slots = (chunk_len() / stride).max(1);
+
+        unsafe {
+            let mut memptr = alloc_sized(slots * stride)?;
+            for slot in memptr.as_mut().chunks_mut(stride) {
+                slot[..CANARY_SIZE].copy_from_slice(&canary);
+            }
+
+            Ok(Chunk {
+                key: SecKey::from_unprotected(memptr)?,
+                count: Cell::new(0),
+                writers: Cell::new(0),
+                prot: Cell::new(Prot::NoAccess),
+                canary,
+                stride
+            })
+        }
+    }
+
+    #[inline]
+    fn slots(&self) -> usize {
+        self.key.ptr.len() / self.stride
+    }
+
+    /// Value of the slot at `index`.
+    #[inline]
+    fn value_ptr(&self, index: usize) -> *mut u8 {
+        unsafe { self.key.ptr.cast::<u8>().as_ptr().add(index * self.stride + CANARY_SIZE) }
+    }
+
+    fn unlock(&self, prot: Prot::Ty) {
+        let count = self.count.get();
+        self.count.set(count + 1);
+        if prot == Prot::ReadWrite {
+            self.writers.set(self.writers.get() + 1);
+        }
+
+        // another key of this chunk may only be readable
+        if count == 0 || (prot == Prot::ReadWrite && self.prot.get() != Prot::ReadWrite) {
+            unsafe { protect(self.key.ptr, prot) };
+            self.prot.set(prot);
+        }
+    }
+
+    /// Release a guard taken with `unlock`, `write` if it was `ReadWrite`.
+    unsafe fn lock(&self, write: bool) {
+        let count = self.count.get() - 1;
+        self.count.set(count);
+        if write {
+            self.writers.set(self.writers.get() - 1);
+        }
+
+        if count == 0 {
+            self.key.check_canary();
+            protect(self.key.ptr, Prot::NoAccess);
+            self.prot.set(Prot::NoAccess);
+        } else if self.writers.get() == 0 && self.prot.get() == Prot::ReadWrite {
+            // only readers of other keys are left
+            protect(self.key.ptr, Prot::ReadOnly);
+            self.prot.set(Prot::ReadOnly);
+        }
+    }
+
+    /// The chunk was zeroed in a child process. The chunk must be unlocked.
+    #[inline]
+    unsafe fn is_wiped(&self) -> bool {
+        self.key.is_wiped()
+    }
+
+    /// Abort if the canary of the slot at `index` was overwritten. The chunk must be unlocked.
+    unsafe fn check_canary(&self, index: usize) {
+        if self.is_wiped() {
+            return;
+        }
+
+        let canary_ptr = self.value_ptr(index).sub(CANARY_SIZE);
+        if ptr::read_unaligned(canary_ptr as *const [u8; CANARY_SIZE]) != self.canary {
+            eprintln!(
+                "seckey: pool canary check failed, memory in front of a pooled key was overwritten\n  \
+                 slot:    {}\n  \
+                 address: {:p}",
+                index,
+                self.value_ptr(index)
+            );
+            process::abort();
+        }
+    }
+}
+
+
+#[derive(Clone, Copy)]
+struct Slot {
+    chunk: usize,
+    index: usize
+}
+
+/// Secure Pool
+///
+/// Packs many small keys into shared locked pages, instead of
+/// the guard pages and locked page `memsec` maps for every `SecKey`.
+/// Each slot starts with a random canary, that is checked whenever
+/// its page is locked again, and a slot is zeroed when its key is dropped.
+///
+/// Keys in the same page share its protection, reading one key makes
+/// its neighbours readable too, and writing one makes them writable.
+/// Use `SecKey` for long-lived secrets, and a pool for many short session keys.
+///
+/// ```
+/// use seckey::SecPool;
+///
+/// let pool = SecPool::new(32).unwrap();
+///
+/// let k1 = pool.alloc([1u8; 32]).unwrap();
+/// let mut k2 = pool.alloc([2u8; 32]).unwrap();
+/// k2.write()[0] = 0;
+///
+/// assert_eq!([1; 32], *k1.read());
+/// assert_eq!(0, k2.read()[0]);
+/// ```
+pub struct SecPool {
+    slot_size: usize,
+    stride: usize,
+    canary: [u8; CANARY_SIZE],
+    // boxed, keys borrow a chunk while the vec grows
+    #[allow(clippy::vec_box)]
+    chunks: RefCell<Vec<Box<Chunk>>>,
+    free: RefCell<Vec<Slot>>
+}
+
+impl SecPool {
+    /// Create a pool for values of at most `slot_size` bytes.
+    ///
+    /// Memory is allocated in chunks of one page as keys are added.
+    pub fn new(slot_size: usize) -> Result<SecPool, SecKeyError> {
+        let mut canary = [0; CANARY_SIZE];
+        getrandom(&mut canary).map_err(|err| SecKeyError::RandomFailed(err.raw_os_error()))?;
+
+        let stride = slot_size.div_ceil(SLOT_ALIGN)
+            .checked_mul(SLOT_ALIGN)
+            .and_then(|size| size.checked_add(CANARY_SIZE))
+            .ok_or(SecKeyError::AllocFailed)?;
+
+        Ok(SecPool {
+            slot_size,
+            stride,
+            canary,
+            chunks: RefCell::new(Vec::new()),
+            free: RefCell::new(Vec::new())
+        })
+    }
+
+    #[inline]
+    pub fn slot_size(&self) -> usize {
+        self.slot_size
+    }
+
+    fn take_slot(&self) -> Result<Slot, SecKeyError> {
+        if let Some(slot) = self.free.borrow_mut().pop() {
+            return Ok(slot);
+        }
+
+        let chunk = Chunk::new(self.stride, self.canary)?;
+        let mut chunks = self.chunks.borrow_mut();
+        let mut free = self.free.borrow_mut();
+
+        // hand out the lowest slot first
+        free.extend((1..chunk.slots()).rev().map(|index| Slot { chunk: chunks.len(), index }));
+        let slot = Slot { chunk: chunks.len(), index: 0 };
+        chunks.push(Box::new(chunk));
+
+        Ok(slot)
+    }
+
+    /// Chunks are boxed and only freed with the pool.
+    #[inline]
+    fn chunk(&self, slot: Slot) -> &Chunk {
+        let chunk: *const Chunk = &*self.chunks.borrow()[slot.chunk];
+        unsafe { &*chunk }
+    }
+
+    /// Move `t` into a free slot, zeroing the original.
+    ///
+    /// On allocation failure `t` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` is larger than the slot size, or needs more than 16 byte alignment.
+    pub fn alloc<T>(&self, mut t: T) -> Result<PooledKey<'_, T>, T> {
+        assert!(
+            mem::size_of::<T>() <= self.slot_size && mem::align_of::<T>() <= SLOT_ALIGN,
+            "{} does not fit a {} byte pool slot",
+            any::type_name::<T>(),
+            self.slot_size
+        );
+
+        let slot = match self.take_slot() {
+            Ok(slot) => slot,
+            Err(_) => return Err(t)
+        };
+        let chunk = self.chunk(slot);
+        let ptr = chunk.value_ptr(slot.index) as *mut T;
+
+        unsafe {
+            chunk.unlock(Prot::ReadWrite);
+            ptr::copy_nonoverlapping(&t, ptr, 1);
+            chunk.lock(true);
+
+            memzero(&mut t as *mut T as *mut u8, mem::size_of::<T>());
+            mem::forget(t);
+
+            Ok(PooledKey {
+                ptr: NonNull::new_unchecked(ptr),
+                chunk,
+                pool: self,
+                slot,
+                writing: Cell::new(false)
+            })
+        }
+    }
+}
+
+impl fmt::Debug for SecPool {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SecPool")
+            .field("slot_size", &self.slot_size)
+            .field("chunks", &self.chunks.borrow().len())
+            .field("free", &self.free.borrow().len())
+            .finish()
+    }
+}
+
+
+/// Pooled Key
+///
+/// A key in a [`SecPool`](struct.SecPool.html) slot, with the same guards as `SecKey`.
+pub struct PooledKey<'a, T> {
+    ptr: NonNull<T>,
+    chunk: &'a Chunk,
+    pool: &'a SecPool,
+    slot: Slot,
+    /// A write guard is alive, it excludes read guards of this key.
+    writing: Cell<bool>
+}
+
+impl<'a, T> PooledKey<'a, T> {
+    /// Borrow Read
+    #[inline]
+    pub fn read(&self) -> SecReadGuard<'_, T> {
+        self.chunk.unlock(Prot::ReadOnly);
+        unsafe {
+            self.chunk.key.forbid_wiped();
+            SecReadGuard::from_raw(self.ptr, self)
+        }
+    }
+
+    /// Borrow Write
+    #[inline]
+    pub fn write(&mut self) -> SecWriteGuard<'_, T> {
+        self.chunk.unlock(Prot::ReadWrite);
+        self.writing.set(true);
+        unsafe {
+            self.chunk.key.forbid_wiped();
+            SecWriteGuard::from_raw(self.ptr, &*self)
+        }
+    }
+}
+
+impl<'a, T> Lock for PooledKey<'a, T> {
+    unsafe fn lock(&self) {
+        self.chunk.check_canary(self.slot.index);
+        self.chunk.lock(self.writing.replace(false));
+    }
+}
+
+impl<'a, T> fmt::Debug for PooledKey<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("PooledKey")
+            .field(&format_args!("{:p}", self.ptr))
+            .finish()
+    }
+}
+
+impl<'a, T> fmt::Pointer for PooledKey<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:p}", self.ptr)
+    }
+}
+
+impl<'a, T> Drop for PooledKey<'a, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.chunk.unlock(Prot::ReadWrite);
+            self.chunk.check_canary(self.slot.index);
+            if !self.chunk.is_wiped() {
+                ptr::drop_in_place(self.ptr.as_ptr());
+            }
+            memzero(self.ptr.cast::<u8>().as_ptr(), self.chunk.stride - CANARY_SIZE);
+            self.chunk.lock(true);
+        }
+
+        self.pool.free.borrow_mut().push(self.slot);
+    }
+}
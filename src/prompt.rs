@@ -0,0 +1,220 @@
+//! Read a passphrase from the terminal straight into protected memory.
+//!
+//! ```no_run
+//! use seckey::prompt;
+//!
+//! let pass = prompt::read_password("Passphrase: ").unwrap();
+//! assert!(!pass.read().is_empty());
+//! ```
+
+use std::fs::{ File, OpenOptions };
+use std::io::{ self, Read, Write };
+use std::{ mem, ptr };
+use std::cell::UnsafeCell;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::os::unix::io::AsRawFd;
+use std::sync::{ Mutex, MutexGuard, PoisonError };
+use libc::{ self, termios, ECHO, TCSAFLUSH };
+use ::{ SecKey, SecString, SecVec, zero };
+
+
+const BACKSPACE: u8 = 0x08;
+const DELETE: u8 = 0x7f;
+const KILL_LINE: u8 = 0x15;
+
+/// Signals that would leave the terminal without echo.
+const SIGNALS: [libc::c_int; 4] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP, libc::SIGQUIT];
+
+/// What the signal handler restores, only written while `ECHO_OFF` is held.
+struct Saved {
+    fd: UnsafeCell<libc::c_int>,
+    termios: UnsafeCell<termios>,
+    actions: UnsafeCell<[libc::sigaction; 4]>,
+    /// The handler ran, echo is on again.
+    restored: AtomicBool
+}
+
+unsafe impl Sync for Saved {}
+
+static SAVED: Saved = unsafe {
+    Saved {
+        fd: UnsafeCell::new(-1),
+        termios: UnsafeCell::new(mem::zeroed()),
+        actions: UnsafeCell::new(mem::zeroed()),
+        restored: AtomicBool::new(false)
+    }
+};
+
+static ECHO_OFF: Mutex<()> = Mutex::new(());
+
+/// Restore the terminal and the previous handlers, then deliver the signal again.
+extern "C" fn restore_and_raise(signum: libc::c_int) {
+    unsafe {
+        libc::tcsetattr(*SAVED.fd.get(), TCSAFLUSH, SAVED.termios.get());
+        for (&signum, action) in SIGNALS.iter().zip((*SAVED.actions.get()).iter()) {
+            libc::sigaction(signum, action, ptr::null_mut());
+        }
+        SAVED.restored.store(true, Ordering::SeqCst);
+        libc::raise(signum);
+    }
+}
+
+/// Turn echo off, and restore the terminal on drop, even if reading panics,
+/// or on a signal that would kill the process.
+///
+/// Canonical mode stays on, so the terminal still handles
+/// line editing, `^D` and `^C`.
+///
+/// If the application handles the signal and carries on, the interrupted read
+/// turns echo off again before it is retried.
+struct EchoOff<'a> {
+    tty: &'a File,
+    _lock: MutexGuard<'static, ()>
+}
+
+impl<'a> EchoOff<'a> {
+    fn enable(tty: &'a File) -> io::Result<EchoOff<'a>> {
+        let fd = tty.as_raw_fd();
+        let lock = ECHO_OFF.lock().unwrap_or_else(PoisonError::into_inner);
+
+        unsafe {
+            let saved = &mut *SAVED.termios.get();
+            if libc::tcgetattr(fd, saved) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+            *SAVED.fd.get() = fd;
+        }
+
+        let echo_off = EchoOff { tty, _lock: lock };
+        echo_off.disable()?;
+        Ok(echo_off)
+    }
+
+    /// Install the handlers and clear `ECHO`.
+    fn disable(&self) -> io::Result<()> {
+        SAVED.restored.store(false, Ordering::SeqCst);
+
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = restore_and_raise as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            libc::sigemptyset(&mut action.sa_mask);
+            for (&signum, old) in SIGNALS.iter().zip((*SAVED.actions.get()).iter_mut()) {
+                libc::sigaction(signum, &action, old);
+            }
+
+            let mut noecho = *SAVED.termios.get();
+            noecho.c_lflag &= !ECHO;
+            if libc::tcsetattr(self.tty.as_raw_fd(), TCSAFLUSH, &noecho) != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a, 'b> Read for &'b EchoOff<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match (&*self.tty).read(buf) {
+            // a handled signal restored the terminal
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted && SAVED.restored.load(Ordering::SeqCst) => {
+                self.disable()?;
+                Err(io::Error::from(io::ErrorKind::Interrupted))
+            },
+            result => result
+        }
+    }
+}
+
+impl<'a> Drop for EchoOff<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            libc::tcsetattr(self.tty.as_raw_fd(), TCSAFLUSH, SAVED.termios.get());
+            for (&signum, action) in SIGNALS.iter().zip((*SAVED.actions.get()).iter()) {
+                libc::sigaction(signum, action, ptr::null_mut());
+            }
+        }
+    }
+}
+
+/// Drop the last UTF-8 char, zeroing its bytes.
+fn pop_char(buf: &mut SecVec) {
+    let len = {
+        let bytes = buf.read();
+        let mut len = bytes.len();
+        while len > 0 {
+            len -= 1;
+            if bytes[len] & 0xc0 != 0x80 {
+                break;
+            }
+        }
+        len
+    };
+
+    buf.truncate(len);
+}
+
+/// Read one line from `reader`, one byte at a time.
+///
+/// Backspace removes the last char and `^U` clears the line.
+/// Bytes are pushed straight into a `SecVec`, only a one byte stack buffer is used.
+///
+/// ```
+/// use seckey::prompt;
+///
+/// let pass = prompt::read_password_from(&b"hunter3\x7f2\nrest"[..]).unwrap();
+/// assert_eq!(&*pass.read(), "hunter2");
+/// ```
+#[track_caller]
+pub fn read_password_from<R: Read>(mut reader: R) -> io::Result<SecKey<str>> {
+    let mut buf = SecVec::new()?;
+    let mut byte = [0; 1];
+
+    let result = loop {
+        match reader.read(&mut byte) {
+            Ok(0) => break Ok(()),
+            Ok(_) => (),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => break Err(err)
+        }
+
+        match byte[0] {
+            b'\n' | b'\r' => break Ok(()),
+            BACKSPACE | DELETE => pop_char(&mut buf),
+            KILL_LINE => buf.clear(),
+            b => {
+                if let Err(err) = buf.try_reserve(1) {
+                    break Err(err.into());
+                }
+                buf.push(b);
+            }
+        }
+    };
+    zero(&mut byte);
+    result?;
+
+    SecString::from_utf8(buf)
+        .map(SecString::into_seckey)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "passphrase is not valid UTF-8"))
+}
+
+/// Print `prompt` to `/dev/tty` and read a passphrase without echo.
+///
+/// Fails if the process has no controlling terminal.
+#[track_caller]
+pub fn read_password(prompt: &str) -> io::Result<SecKey<str>> {
+    let tty = OpenOptions::new().read(true).write(true).open("/dev/tty")?;
+
+    (&tty).write_all(prompt.as_bytes())?;
+    (&tty).flush()?;
+
+    let result = {
+        let echo_off = EchoOff::enable(&tty)?;
+        read_password_from(&echo_off)
+    };
+
+    // the newline was not echoed
+    (&tty).write_all(b"\n")?;
+
+    result
+}
@@ -0,0 +1,102 @@
+use core::slice;
+use getrandom::getrandom;
+#[cfg(feature = "rand_core")] use rand_core::{ RngCore, CryptoRng };
+use seckey::alloc_sized;
+use ::{ SecKey, SecKeyError, Policy };
+
+
+/// Fill the allocation in place, so the bytes never touch the stack.
+type Fill<'a> = &'a mut dyn FnMut(&mut [u8]) -> Result<(), SecKeyError>;
+
+#[inline]
+fn os_fill(dst: &mut [u8]) -> Result<(), SecKeyError> {
+    getrandom(dst).map_err(|err| SecKeyError::RandomFailed(err.raw_os_error()))
+}
+
+#[cfg(feature = "rand_core")]
+#[inline]
+fn rng_fill<R: RngCore + CryptoRng>(rng: &mut R, dst: &mut [u8]) -> Result<(), SecKeyError> {
+    rng.try_fill_bytes(dst).map_err(|err| SecKeyError::RandomFailed(err.raw_os_error()))
+}
+
+#[track_caller]
+fn array_with<const N: usize>(policy: &Policy, fill: Fill) -> Result<SecKey<[u8; N]>, SecKeyError> {
+    let mut result = Ok(());
+    let key = unsafe {
+        SecKey::with_policy(policy, |memptr: *mut [u8; N]| {
+            result = fill(slice::from_raw_parts_mut(memptr as *mut u8, N))
+        })?
+    };
+
+    // on failure the key is zeroed and freed
+    result.map(|()| key)
+}
+
+#[track_caller]
+fn slice_with(len: usize, fill: Fill) -> Result<SecKey<[u8]>, SecKeyError> {
+    unsafe {
+        let mut memptr = alloc_sized(len)?;
+        let result = fill(memptr.as_mut());
+        let key = SecKey::from_unprotected(memptr)?;
+
+        result.map(|()| key)
+    }
+}
+
+impl<const N: usize> SecKey<[u8; N]> {
+    /// Generate a random key with the OS random number generator.
+    ///
+    /// The bytes are written straight into the secure heap.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let k1 = SecKey::<[u8; 32]>::random().unwrap();
+    /// let k2 = SecKey::<[u8; 32]>::random().unwrap();
+    /// assert_ne!(*k1.read(), *k2.read());
+    /// ```
+    #[track_caller]
+    pub fn random() -> Result<SecKey<[u8; N]>, SecKeyError> {
+        array_with(&Policy::default(), &mut os_fill)
+    }
+
+    #[track_caller]
+    pub(crate) fn random_with_policy(policy: &Policy) -> Result<SecKey<[u8; N]>, SecKeyError> {
+        array_with(policy, &mut os_fill)
+    }
+
+    /// Generate a random key with a user-supplied RNG.
+    ///
+    /// The bytes are written straight into the secure heap.
+    #[cfg(feature = "rand_core")]
+    #[track_caller]
+    pub fn from_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Result<SecKey<[u8; N]>, SecKeyError> {
+        array_with(&Policy::default(), &mut |dst| rng_fill(rng, dst))
+    }
+}
+
+impl SecKey<[u8]> {
+    /// Generate a random key of `len` bytes with the OS random number generator.
+    ///
+    /// The bytes are written straight into the secure heap.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let k = SecKey::<[u8]>::random(64).unwrap();
+    /// assert_eq!(64, k.read().len());
+    /// ```
+    #[track_caller]
+    pub fn random(len: usize) -> Result<SecKey<[u8]>, SecKeyError> {
+        slice_with(len, &mut os_fill)
+    }
+
+    /// Generate a random key of `len` bytes with a user-supplied RNG.
+    ///
+    /// The bytes are written straight into the secure heap.
+    #[cfg(feature = "rand_core")]
+    #[track_caller]
+    pub fn from_rng<R: RngCore + CryptoRng>(len: usize, rng: &mut R) -> Result<SecKey<[u8]>, SecKeyError> {
+        slice_with(len, &mut |dst| rng_fill(rng, dst))
+    }
+}
@@ -0,0 +1,153 @@
+use core::cmp;
+use core::convert::TryFrom;
+use std::fs::File;
+use std::io::{ self, Read };
+use std::path::Path;
+use ::{ SecKey, SecVec, zero };
+
+
+/// Default limit for `SecKey::read_file`.
+const DEFAULT_MAX_LEN: usize = 64 * 1024;
+
+const CHUNK_LEN: usize = 256;
+
+fn read_chunks<R: Read>(reader: &mut R, vec: &mut SecVec, chunk: &mut [u8], max_len: usize) -> io::Result<()> {
+    loop {
+        // read one byte past the limit to detect an oversized input
+        let want = cmp::min(chunk.len(), (max_len - vec.len()).saturating_add(1));
+
+        let n = match reader.read(&mut chunk[..want]) {
+            Ok(0) => return Ok(()),
+            Ok(n) => n,
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err)
+        };
+
+        if vec.len() + n > max_len {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "secret is longer than max_len"));
+        }
+
+        vec.try_reserve(n)?;
+        vec.extend_from_slice(&chunk[..n]);
+    }
+}
+
+impl SecKey<[u8]> {
+    /// Read at most `max_len` bytes from `reader` into a new key.
+    ///
+    /// Data is copied through a small stack buffer that is zeroed afterwards.
+    /// Returns an `InvalidData` error if `reader` holds more than `max_len` bytes.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let k = SecKey::from_reader(&b"secret"[..], 32).unwrap();
+    /// assert_eq!(*k.read(), *b"secret");
+    ///
+    /// assert!(SecKey::from_reader(&b"secret"[..], 4).is_err());
+    /// ```
+    #[track_caller]
+    pub fn from_reader<R: Read>(reader: R, max_len: usize) -> io::Result<SecKey<[u8]>> {
+        from_reader_with_capacity(reader, max_len, 0)
+    }
+
+    /// Read a secret file, eg. from `$CREDENTIALS_DIRECTORY` or a Kubernetes secret mount.
+    ///
+    /// On Unix the file must not be accessible by group or others,
+    /// and at most 64 KiB are read.
+    /// Use [`SecretFile`](struct.SecretFile.html) to change that.
+    #[track_caller]
+    pub fn read_file<P: AsRef<Path>>(path: P) -> io::Result<SecKey<[u8]>> {
+        SecretFile::new().read(path)
+    }
+}
+
+#[track_caller]
+fn from_reader_with_capacity<R: Read>(mut reader: R, max_len: usize, cap: usize) -> io::Result<SecKey<[u8]>> {
+    let mut vec = SecVec::with_capacity(cmp::min(cap, max_len))?;
+    let mut chunk = [0; CHUNK_LEN];
+
+    let result = read_chunks(&mut reader, &mut vec, &mut chunk, max_len);
+    zero(&mut chunk);
+
+    result.map(|()| vec.into_seckey())
+}
+
+
+/// Options for reading a secret file.
+///
+/// ```no_run
+/// use seckey::SecretFile;
+///
+/// let k = SecretFile::new()
+///     .max_len(4096)
+///     .allow_permissive(true)
+///     .read("/run/secrets/api-key")
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SecretFile {
+    max_len: usize,
+    allow_permissive: bool
+}
+
+impl Default for SecretFile {
+    fn default() -> SecretFile {
+        SecretFile {
+            max_len: DEFAULT_MAX_LEN,
+            allow_permissive: false
+        }
+    }
+}
+
+impl SecretFile {
+    #[inline]
+    pub fn new() -> SecretFile {
+        SecretFile::default()
+    }
+
+    /// Fail if the file is longer than `max_len` bytes.
+    #[inline]
+    pub fn max_len(&mut self, max_len: usize) -> &mut SecretFile {
+        self.max_len = max_len;
+        self
+    }
+
+    /// Accept files that are accessible by group or others.
+    ///
+    /// Kubernetes mounts secrets with mode `0644` unless `defaultMode` is set.
+    #[inline]
+    pub fn allow_permissive(&mut self, allow: bool) -> &mut SecretFile {
+        self.allow_permissive = allow;
+        self
+    }
+
+    #[track_caller]
+    pub fn read<P: AsRef<Path>>(&self, path: P) -> io::Result<SecKey<[u8]>> {
+        let file = File::open(path)?;
+        let metadata = file.metadata()?;
+
+        if !self.allow_permissive {
+            check_mode(&metadata)?;
+        }
+
+        let cap = usize::try_from(metadata.len()).unwrap_or(usize::MAX);
+        from_reader_with_capacity(file, self.max_len, cap)
+    }
+}
+
+#[cfg(unix)]
+fn check_mode(metadata: &std::fs::Metadata) -> io::Result<()> {
+    use std::os::unix::fs::MetadataExt;
+
+    if metadata.mode() & 0o077 != 0 {
+        Err(io::Error::new(io::ErrorKind::PermissionDenied, "secret file is accessible by group or others"))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+fn check_mode(_metadata: &std::fs::Metadata) -> io::Result<()> {
+    Ok(())
+}
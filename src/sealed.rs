@@ -0,0 +1,240 @@
+use core::{ fmt, mem, slice };
+use core::cell::Cell;
+use core::sync::atomic::{ AtomicU64, Ordering };
+use std::process;
+use std::sync::OnceLock;
+use getrandom::getrandom;
+use memsec::Prot;
+use policy::protect;
+use chacha20::{ self, KEY_LENGTH, NONCE_LENGTH };
+use seckey::Lock;
+use ::{ SecKey, SecKeyError, SecReadGuard, SecWriteGuard, SyncSecKey, Policy };
+
+
+/// Per-process pre-key, never leaves the secure heap.
+///
+/// It is not zeroed in child processes, so a child can still unseal
+/// the keys it inherits.
+static PREKEY: OnceLock<SyncSecKey<[u8; KEY_LENGTH]>> = OnceLock::new();
+
+/// Every seal takes a fresh nonce, so the keystream is never reused.
+static NONCE: AtomicU64 = AtomicU64::new(0);
+
+/// Random nonce prefix, with the process id it was drawn for in the high half.
+///
+/// A child inherits the pre-key and the counter, so it draws its own prefix,
+/// and never seals with a nonce of its parent.
+static PREFIX: AtomicU64 = AtomicU64::new(0);
+
+fn prekey() -> Result<&'static SyncSecKey<[u8; KEY_LENGTH]>, SecKeyError> {
+    if let Some(prekey) = PREKEY.get() {
+        return Ok(prekey);
+    }
+
+    let key = SecKey::<[u8; KEY_LENGTH]>::random_with_policy(Policy::new().wipe_on_fork(false))?;
+
+    // another thread may win the race, then this key is dropped
+    let _ = PREKEY.set(SyncSecKey::from(key));
+
+    Ok(PREKEY.get().expect("pre-key is set"))
+}
+
+fn prefix() -> u32 {
+    let pid = process::id();
+    let current = PREFIX.load(Ordering::Acquire);
+    if (current >> 32) as u32 == pid {
+        return current as u32;
+    }
+
+    let mut prefix = current as u32;
+    while prefix == current as u32 {
+        let mut bytes = [0; 4];
+        if let Err(err) = getrandom(&mut bytes) {
+            panic!("{}", SecKeyError::RandomFailed(err.raw_os_error()));
+        }
+        prefix = u32::from_le_bytes(bytes);
+    }
+
+    PREFIX.store(u64::from(pid) << 32 | u64::from(prefix), Ordering::Release);
+    prefix
+}
+
+fn next_nonce() -> [u8; NONCE_LENGTH] {
+    let mut nonce = [0; NONCE_LENGTH];
+    nonce[..8].copy_from_slice(&NONCE.fetch_add(1, Ordering::Relaxed).to_le_bytes());
+    nonce[8..].copy_from_slice(&prefix().to_le_bytes());
+    nonce
+}
+
+
+/// Sealed Secure Key
+///
+/// Same as [`SecKey`](struct.SecKey.html), but while no guard is alive
+/// the value is also encrypted with ChaCha20 under a per-process pre-key,
+/// like OpenSSH shielded private keys.
+/// A core dump or a raw memory read only sees the ciphertext of `T` itself,
+/// the heap data of a `Vec`, `String` or `Box` stays in plain text outside
+/// the secure heap. Seal inline values, arrays, `[u8]` or `str`.
+///
+/// The value is decrypted when the first guard is taken,
+/// and encrypted again with a fresh nonce when the last guard is dropped.
+///
+/// ```
+/// use seckey::SealedKey;
+///
+/// let mut secpass = SealedKey::new([8u8; 8]).unwrap();
+/// assert_eq!([8u8; 8], *secpass.read());
+///
+/// secpass.write()[0] = 0;
+/// assert_eq!([0, 8, 8, 8, 8, 8, 8, 8], *secpass.read());
+/// ```
+pub struct SealedKey<T: ?Sized> {
+    count: Cell<usize>,
+    nonce: Cell<[u8; NONCE_LENGTH]>,
+    len: usize,
+    key: SecKey<T>
+}
+
+impl<T> SealedKey<T> {
+    /// Returns the value if the pre-key or the secure allocation fails.
+    #[track_caller]
+    pub fn new(t: T) -> Result<SealedKey<T>, T> {
+        if prekey().is_err() {
+            return Err(t);
+        }
+
+        SecKey::new(t).map(SealedKey::from)
+    }
+}
+
+impl<T: ?Sized> From<SecKey<T>> for SealedKey<T> {
+    /// # Panics
+    ///
+    /// Panics if the pre-key can not be created.
+    ///
+    /// ```
+    /// use seckey::{ SecKey, SealedKey };
+    ///
+    /// let mut unprotected = [1u8; 2];
+    /// let k = SecKey::from_bytes(&mut unprotected[..]).unwrap();
+    /// let k = SealedKey::from(k);
+    /// assert_eq!(*k.read(), [1u8; 2]);
+    /// ```
+    fn from(key: SecKey<T>) -> SealedKey<T> {
+        if let Err(err) = prekey() {
+            panic!("{}", err);
+        }
+
+        // only the metadata is read, the value stays protected
+        let len = unsafe { mem::size_of_val(key.ptr.as_ref()) };
+        let key = SealedKey {
+            count: Cell::new(0),
+            nonce: Cell::new([0; NONCE_LENGTH]),
+            len,
+            key
+        };
+
+        unsafe {
+            protect(key.key.ptr, Prot::ReadWrite);
+            key.seal();
+            protect(key.key.ptr, key.key.policy.idle);
+        }
+
+        key
+    }
+}
+
+impl<T: ?Sized> SealedKey<T> {
+    /// The value must be writable.
+    unsafe fn apply_keystream(&self) {
+        let prekey = PREKEY.get().expect("pre-key is set");
+        let data = slice::from_raw_parts_mut(self.key.ptr.cast::<u8>().as_ptr(), self.len);
+        chacha20::apply_keystream(&prekey.read(), &self.nonce.get(), data);
+    }
+
+    /// The value must be writable and unsealed.
+    unsafe fn seal(&self) {
+        self.nonce.set(next_nonce());
+        self.apply_keystream();
+    }
+
+    fn unlock(&self, prot: Prot::Ty) {
+        let count = self.count.get();
+        self.count.set(count + 1);
+        if count == 0 {
+            unsafe {
+                protect(self.key.ptr, Prot::ReadWrite);
+                self.key.forbid_wiped();
+                self.apply_keystream();
+                if prot != Prot::ReadWrite {
+                    protect(self.key.ptr, prot);
+                }
+            }
+        }
+    }
+
+    /// Borrow Read
+    ///
+    /// ```
+    /// use seckey::SealedKey;
+    ///
+    /// let secpass = SealedKey::new([8u8; 8]).unwrap();
+    /// let rpass1 = secpass.read();
+    /// let rpass2 = secpass.read();
+    /// assert_eq!(*rpass1, *rpass2);
+    /// ```
+    #[inline]
+    pub fn read(&self) -> SecReadGuard<'_, T> {
+        self.unlock(Prot::ReadOnly);
+        unsafe { SecReadGuard::from_raw(self.key.ptr, self) }
+    }
+
+    /// Borrow Write
+    #[inline]
+    pub fn write(&mut self) -> SecWriteGuard<'_, T> {
+        self.unlock(Prot::ReadWrite);
+        unsafe { SecWriteGuard::from_raw(self.key.ptr, self) }
+    }
+}
+
+impl<T: ?Sized> Lock for SealedKey<T> {
+    unsafe fn lock(&self) {
+        let count = self.count.get();
+        self.count.set(count - 1);
+        if count <= 1 {
+            protect(self.key.ptr, Prot::ReadWrite);
+            self.key.check_canary();
+            self.seal();
+            protect(self.key.ptr, self.key.policy.idle);
+        }
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for SealedKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SealedKey")
+            .field(&format_args!("{:p}", self.key.ptr))
+            .field(&self.count)
+            .finish()
+    }
+}
+
+impl<T: ?Sized> fmt::Pointer for SealedKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:p}", self.key.ptr)
+    }
+}
+
+impl<T: ?Sized> Drop for SealedKey<T> {
+    fn drop(&mut self) {
+        // unseal, so `SecKey` drops the plaintext value
+        unsafe {
+            protect(self.key.ptr, Prot::ReadWrite);
+
+            // a key zeroed in a child process is not sealed, and is not dropped
+            if !self.key.is_wiped() {
+                self.apply_keystream();
+            }
+        }
+    }
+}
@@ -2,7 +2,14 @@ use core::{ fmt, mem, str };
 use core::ptr::{ self, NonNull };
 use core::ops::{ Deref, DerefMut };
 use core::cell::Cell;
-use memsec::{ memzero, malloc, malloc_sized, free, mprotect, Prot };
+use core::marker::PhantomData;
+use memsec::{ memzero, malloc, malloc_sized, free, Prot };
+use cmpkey;
+use canary;
+use policy::{ self, Policy, protect };
+#[cfg(debug_assertions)] use canary::Origin;
+#[cfg(feature = "stats")] use stats;
+use ::{ SecKeyError, Choice, NoPadding };
 
 
 /// Secure Key
@@ -12,11 +19,24 @@ use memsec::{ memzero, malloc, malloc_sized, free, mprotect, Prot };
 /// Note that this does not protect data outside of the secure heap.
 ///
 /// More docs see [Secure memory · libsodium](https://download.libsodium.org/doc/helpers/memory_management.html).
+///
+/// In debug builds the canary in front of the value is checked every time
+/// the key is locked again. If it was overwritten, the process aborts with
+/// a report naming the type of the key and where it was created.
+///
+/// A child process sees every key zeroed, unless its policy turns
+/// [`wipe_on_fork`](struct.Policy.html#method.wipe_on_fork) off.
+/// There a zeroed key can only be dropped, `read` and `write` abort.
 pub struct SecKey<T: ?Sized> {
-    ptr: NonNull<T>,
-    count: Cell<usize>
+    pub(crate) ptr: NonNull<T>,
+    count: Cell<usize>,
+    prot: Cell<Prot::Ty>,
+    pub(crate) policy: Policy,
+    #[cfg(debug_assertions)]
+    pub(crate) origin: Origin
 }
 
+
 impl<T> SecKey<T> {
     /// ```
     /// use seckey::{ free, SecKey };
@@ -29,19 +49,46 @@ impl<T> SecKey<T> {
     ///     });
     /// assert_eq!([1, 2, 3], *k.read());
     /// ```
-    pub fn new(mut t: T) -> Result<SecKey<T>, T> {
+    #[track_caller]
+    #[inline]
+    pub fn new(t: T) -> Result<SecKey<T>, T> {
+        Self::try_new(t).map_err(|(t, _)| t)
+    }
+
+    /// Same as `new`, but the error is returned along with `t`.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let k = SecKey::try_new([1, 2, 3])
+    ///     .unwrap_or_else(|(_, err)| panic!("{}", err));
+    /// assert_eq!([1, 2, 3], *k.read());
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn try_new(t: T) -> Result<SecKey<T>, (T, SecKeyError)> {
+        Self::new_with_policy(&Policy::default(), t)
+    }
+
+    #[track_caller]
+    pub(crate) fn new_with_policy(policy: &Policy, mut t: T) -> Result<SecKey<T>, (T, SecKeyError)> {
         unsafe {
-            match Self::from_ptr(&t) {
-                Some(output) => {
+            match Self::copy_with_policy(policy, &t) {
+                Ok(output) => {
                     memzero(&mut t as *mut T as *mut u8, mem::size_of::<T>());
                     mem::forget(t);
                     Ok(output)
                 },
-                None => Err(t)
+                Err(err) => Err((t, err))
             }
         }
     }
 
+    /// # Safety
+    ///
+    /// `t` must be valid for reads, and the bitwise copy must not
+    /// break ownership of `T` (see `ptr::read`).
+    ///
     /// ```
     /// use seckey::SecKey;
     ///
@@ -51,33 +98,54 @@ impl<T> SecKey<T> {
     /// assert_eq!([1, 2, 3], *k.read());
     /// ```
     #[inline]
-    pub unsafe fn from_ptr(t: *const T) -> Option<SecKey<T>> {
-        Self::with(move |memptr| ptr::copy_nonoverlapping(t, memptr, 1))
+    #[track_caller]
+    pub unsafe fn from_ptr(t: *const T) -> Result<SecKey<T>, SecKeyError> {
+        Self::copy_with_policy(&Policy::default(), t)
+    }
+
+    /// On failure the copy is freed without being dropped, `t` still owns the value.
+    #[track_caller]
+    unsafe fn copy_with_policy(policy: &Policy, t: *const T) -> Result<SecKey<T>, SecKeyError> {
+        let memptr = alloc(malloc(), mem::size_of::<T>(), policy)?;
+
+        ptr::copy_nonoverlapping(t, memptr.as_ptr(), 1);
+
+        SecKey::protect(memptr, policy, false)
     }
 
+    /// # Safety
+    ///
+    /// `f` receives uninitialized memory and must fully initialize it.
+    ///
     /// ```
     /// use seckey::SecKey;
     ///
     /// let k: SecKey<u32> = unsafe { SecKey::with(|ptr| *ptr = 1).unwrap() };
     /// assert_eq!(1, *k.read());
     /// ```
-    pub unsafe fn with<F>(f: F) -> Option<SecKey<T>>
+    #[track_caller]
+    #[inline]
+    pub unsafe fn with<F>(f: F) -> Result<SecKey<T>, SecKeyError>
         where F: FnOnce(*mut T)
     {
-        let memptr = malloc()?;
+        Self::with_policy(&Policy::default(), f)
+    }
+
+    #[track_caller]
+    pub(crate) unsafe fn with_policy<F>(policy: &Policy, f: F) -> Result<SecKey<T>, SecKeyError>
+        where F: FnOnce(*mut T)
+    {
+        let memptr = alloc(malloc(), mem::size_of::<T>(), policy)?;
 
         f(memptr.as_ptr());
-        mprotect(memptr, Prot::NoAccess);
 
-        Some(SecKey {
-            ptr: memptr,
-            count: Cell::new(0)
-        })
+        SecKey::protect(memptr, policy, true)
     }
 }
 
 impl<T: Copy> SecKey<T> {
-    pub fn from_ref(t: &T) -> Option<SecKey<T>> {
+    #[track_caller]
+    pub fn from_ref(t: &T) -> Result<SecKey<T>, SecKeyError> {
         unsafe { Self::from_ptr(t) }
     }
 }
@@ -89,7 +157,8 @@ impl<T: Default> SecKey<T> {
     /// let k: SecKey<u32> = SecKey::with_default(|ptr| *ptr += 1).unwrap();
     /// assert_eq!(1, *k.read());
     /// ```
-    pub fn with_default<F>(f: F) -> Option<SecKey<T>>
+    #[track_caller]
+    pub fn with_default<F>(f: F) -> Result<SecKey<T>, SecKeyError>
         where F: FnOnce(&mut T)
     {
         unsafe {
@@ -103,7 +172,7 @@ impl<T: Default> SecKey<T> {
 
 impl SecKey<[u8]> {
     /// On success `src` is zeroed and a new protected `SecKey<[u8]>` is returned.
-    /// On failure `src` remains untouched and `None` is returned.
+    /// On failure `src` remains untouched and the error is returned.
     ///
     /// ```
     /// use seckey::SecKey;
@@ -114,9 +183,16 @@ impl SecKey<[u8]> {
     /// assert_eq!(unprotected, [0u8; 2]);
     /// assert_eq!(*k.read(), [1u8; 2]);
     /// ```
-    pub fn from_bytes(src: &mut [u8]) -> Option<SecKey<[u8]>> {
+    #[track_caller]
+    #[inline]
+    pub fn from_bytes(src: &mut [u8]) -> Result<SecKey<[u8]>, SecKeyError> {
+        Self::from_bytes_with_policy(&Policy::default(), src)
+    }
+
+    #[track_caller]
+    pub(crate) fn from_bytes_with_policy(policy: &Policy, src: &mut [u8]) -> Result<SecKey<[u8]>, SecKeyError> {
         unsafe {
-            let mut memptr = malloc_sized(src.len())?;
+            let mut memptr = alloc_sized_with(src.len(), policy)?;
 
             // copy secret from source
             ptr::copy_nonoverlapping(
@@ -126,22 +202,19 @@ impl SecKey<[u8]> {
             );
 
             // protect secret
-            mprotect(memptr, Prot::NoAccess);
+            let key = SecKey::from_unprotected_with(memptr, policy)?;
 
             // zero original source
             memzero(src.as_mut_ptr(), src.len());
 
-            Some(SecKey {
-                ptr: memptr,
-                count: Cell::new(0),
-            })
+            Ok(key)
         }
     }
 }
 
 impl SecKey<str> {
     /// On success `src` is zeroed and a new protected `SecKey<str>` is returned.
-    /// On failure `src` remains untouched and `None` is returned.
+    /// On failure `src` remains untouched and the error is returned.
     ///
     /// ```
     /// use seckey::SecKey;
@@ -152,10 +225,12 @@ impl SecKey<str> {
     /// assert_eq!(&unprotected, "\0\0\0");
     /// assert_eq!(&*k.read(), "abc");
     /// ```
-    pub fn from_str(src: &mut str) -> Option<SecKey<str>> {
+    #[allow(clippy::should_implement_trait)]
+    #[track_caller]
+    pub fn from_str(src: &mut str) -> Result<SecKey<str>, SecKeyError> {
         unsafe {
             let src = src.as_bytes_mut();
-            let mut memptr = malloc_sized(src.len())?;
+            let mut memptr = alloc_sized(src.len())?;
 
             // copy secret from source
             ptr::copy_nonoverlapping(
@@ -168,27 +243,134 @@ impl SecKey<str> {
             );
 
             // protect secret
-            mprotect(strptr, Prot::NoAccess);
+            let key = SecKey::from_unprotected(strptr)?;
 
             // zero original source
             memzero(src.as_mut_ptr(), src.len());
 
-            Some(SecKey {
-                ptr: strptr,
-                count: Cell::new(0),
-            })
+            Ok(key)
         }
     }
 }
 
+/// Allocate with `memsec` and apply `policy` to the memory.
+#[inline]
+unsafe fn alloc<T: ?Sized>(memptr: Option<NonNull<T>>, size: usize, policy: &Policy) -> Result<NonNull<T>, SecKeyError> {
+    let memptr = memptr.ok_or(SecKeyError::AllocFailed)?;
+
+    if let Err(err) = policy.apply(memptr.cast(), size) {
+        policy.release(memptr.cast(), size);
+        free(memptr);
+        return Err(err);
+    }
+
+    #[cfg(feature = "stats")]
+    stats::register(memptr, size, policy.lock_memory);
+
+    Ok(memptr)
+}
+
+#[inline]
+pub(crate) unsafe fn alloc_sized(size: usize) -> Result<NonNull<[u8]>, SecKeyError> {
+    alloc_sized_with(size, &Policy::default())
+}
+
+#[inline]
+pub(crate) unsafe fn alloc_sized_with(size: usize, policy: &Policy) -> Result<NonNull<[u8]>, SecKeyError> {
+    alloc(malloc_sized(size), size, policy)
+}
+
 impl<T: ?Sized> SecKey<T> {
+    /// Take ownership of a protected `memsec` allocation.
+    #[track_caller]
     #[inline]
-    unsafe fn lock(&self) {
+    pub(crate) unsafe fn from_raw(ptr: NonNull<T>, policy: Policy) -> SecKey<T> {
+        #[cfg(feature = "stats")]
+        stats::retype(ptr);
+
+        SecKey {
+            ptr,
+            count: Cell::new(0),
+            prot: Cell::new(policy.idle),
+            policy,
+            #[cfg(debug_assertions)]
+            origin: Origin::new::<T>()
+        }
+    }
+
+    /// Protect an initialized `memsec` allocation and take ownership of it.
+    ///
+    /// On failure the allocation is freed, the value is not dropped.
+    #[track_caller]
+    #[inline]
+    pub(crate) unsafe fn from_unprotected(ptr: NonNull<T>) -> Result<SecKey<T>, SecKeyError> {
+        SecKey::from_unprotected_with(ptr, &Policy::default())
+    }
+
+    #[track_caller]
+    #[inline]
+    pub(crate) unsafe fn from_unprotected_with(ptr: NonNull<T>, policy: &Policy) -> Result<SecKey<T>, SecKeyError> {
+        SecKey::protect(ptr, policy, false)
+    }
+
+    /// The value is only dropped on failure if it is `owned` by the allocation,
+    /// not a bitwise copy of a value the caller gets back.
+    #[track_caller]
+    unsafe fn protect(ptr: NonNull<T>, policy: &Policy, owned: bool) -> Result<SecKey<T>, SecKeyError> {
+        canary::record(ptr);
+
+        if protect(ptr, policy.idle) {
+            Ok(SecKey::from_raw(ptr, *policy))
+        } else {
+            let errno = SecKeyError::last_errno();
+            if owned {
+                ptr::drop_in_place(ptr.as_ptr());
+            }
+            policy.release(ptr.cast(), mem::size_of_val(ptr.as_ref()));
+            #[cfg(feature = "stats")]
+            stats::unregister(ptr);
+            free(ptr);
+            Err(SecKeyError::ProtectFailed(errno))
+        }
+    }
+
+    /// The key was zeroed in a child process.
+    ///
+    /// The key must be unlocked.
+    #[inline]
+    pub(crate) unsafe fn is_wiped(&self) -> bool {
+        self.policy.wipe_on_fork && canary::is_wiped(self.ptr)
+    }
+
+    /// Abort if the key was zeroed in a child process.
+    ///
+    /// The key must be unlocked.
+    #[inline]
+    pub(crate) unsafe fn forbid_wiped(&self) {
+        if self.policy.wipe_on_fork {
+            canary::forbid_wiped(self.ptr);
+        }
+    }
+
+    /// Abort if the canary in front of the value was overwritten, debug builds only.
+    ///
+    /// The key must be unlocked.
+    #[inline]
+    pub(crate) unsafe fn check_canary(&self) {
+        #[cfg(debug_assertions)]
+        canary::check(self.ptr, &self.origin, self.policy.wipe_on_fork);
+    }
+
+    #[inline]
+    fn unlock(&self, prot: Prot::Ty) {
         let count = self.count.get();
-        self.count.set(count - 1);
-        if count <= 1 {
-            mprotect(self.ptr, Prot::NoAccess);
+        self.count.set(count + 1);
+        if count == 0 && !policy::covers(self.prot.get(), prot) {
+            unsafe { protect(self.ptr, prot) };
+            self.prot.set(prot);
         }
+
+        unsafe { self.forbid_wiped() };
     }
 
     /// Borrow Read
@@ -200,14 +382,9 @@ impl<T: ?Sized> SecKey<T> {
     /// assert_eq!([8u8; 8], *secpass.read());
     /// ```
     #[inline]
-    pub fn read(&self) -> SecReadGuard<T> {
-        let count = self.count.get();
-        self.count.set(count + 1);
-        if count == 0 {
-            unsafe { mprotect(self.ptr, Prot::ReadOnly) };
-        }
-
-        SecReadGuard(self)
+    pub fn read(&self) -> SecReadGuard<'_, T> {
+        self.unlock(Prot::ReadOnly);
+        SecReadGuard { ptr: self.ptr, key: self }
     }
 
     /// Borrow Write
@@ -221,17 +398,122 @@ impl<T: ?Sized> SecKey<T> {
     /// assert_eq!([0, 8, 8, 8, 8, 8, 8, 8], *wpass);
     /// ```
     #[inline]
-    pub fn write(&mut self) -> SecWriteGuard<T> {
+    pub fn write(&mut self) -> SecWriteGuard<'_, T> {
+        self.unlock(Prot::ReadWrite);
+        SecWriteGuard { ptr: self.ptr, key: self, _marker: PhantomData }
+    }
+
+    /// Scoped Read
+    ///
+    /// The value is only readable while `f` runs, and is protected again
+    /// even if `f` panics. References to the value can not escape `f`.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let secpass = SecKey::new([8u8; 8]).unwrap();
+    /// let sum = secpass.with_read(|pass| pass.iter().map(|&n| u32::from(n)).sum::<u32>());
+    /// assert_eq!(64, sum);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use seckey::SecKey;
+    ///
+    /// let secpass = SecKey::new([8u8; 8]).unwrap();
+    /// let leaked = secpass.with_read(|pass| &pass[0]);
+    /// ```
+    #[inline]
+    pub fn with_read<F, R>(&self, f: F) -> R
+        where F: FnOnce(&T) -> R
+    {
+        f(&self.read())
+    }
+
+    /// Scoped Write
+    ///
+    /// The value is only writable while `f` runs, and is protected again
+    /// even if `f` panics. References to the value can not escape `f`.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let mut secpass = SecKey::new([8u8; 8]).unwrap();
+    /// secpass.with_write(|pass| pass[0] = 0);
+    /// assert_eq!([0, 8, 8, 8, 8, 8, 8, 8], *secpass.read());
+    /// ```
+    #[inline]
+    pub fn with_write<F, R>(&mut self, f: F) -> R
+        where F: FnOnce(&mut T) -> R
+    {
+        f(&mut self.write())
+    }
+}
+
+/// Reprotect a key when its last guard is dropped.
+///
+/// Guards hold this as a trait object, so a mapped guard
+/// does not need to know the type of the key.
+pub(crate) trait Lock {
+    unsafe fn lock(&self);
+}
+
+impl<T: ?Sized> Lock for SecKey<T> {
+    #[inline]
+    unsafe fn lock(&self) {
+        self.check_canary();
+
         let count = self.count.get();
-        self.count.set(count + 1);
-        if count == 0 {
-            unsafe { mprotect(self.ptr, Prot::ReadWrite) };
+        self.count.set(count - 1);
+        if count <= 1 && self.prot.get() != self.policy.idle {
+            protect(self.ptr, self.policy.idle);
+            self.prot.set(self.policy.idle);
         }
+    }
+}
+
+impl<T: ?Sized + NoPadding> SecKey<T> {
+    /// Constant time equality with a plain value, see `CmpKey::ct_eq`.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let k = SecKey::new([1u8; 4]).unwrap();
+    /// assert!(bool::from(k.ct_eq(&[1; 4])));
+    /// assert!(!bool::from(k.ct_eq(&[2; 4])));
+    /// ```
+    pub fn ct_eq(&self, rhs: &T) -> Choice {
+        cmpkey::ct_eq(&*self.read(), rhs)
+    }
 
-        SecWriteGuard(self)
+    /// Constant time equality with another key, see `CmpKey::ct_eq`.
+    ///
+    /// ```
+    /// use seckey::SecKey;
+    ///
+    /// let k1 = SecKey::new([1u8; 4]).unwrap();
+    /// let k2 = SecKey::new([1u8; 4]).unwrap();
+    /// assert!(bool::from(k1.ct_eq_key(&k2)));
+    /// assert!(k1 == k2);
+    /// ```
+    pub fn ct_eq_key(&self, rhs: &SecKey<T>) -> Choice {
+        self.ct_eq(&rhs.read())
     }
 }
 
+impl<T: ?Sized + NoPadding> PartialEq<T> for SecKey<T> {
+    fn eq(&self, rhs: &T) -> bool {
+        self.ct_eq(rhs).into()
+    }
+}
+
+impl<T: ?Sized + NoPadding> PartialEq<SecKey<T>> for SecKey<T> {
+    fn eq(&self, rhs: &SecKey<T>) -> bool {
+        self.ct_eq_key(rhs).into()
+    }
+}
+
+impl<T: ?Sized + NoPadding> Eq for SecKey<T> {}
+
 impl<T: ?Sized> fmt::Debug for SecKey<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_tuple("SecKey")
@@ -250,8 +532,18 @@ impl<T: ?Sized> fmt::Pointer for SecKey<T> {
 impl<T: ?Sized> Drop for SecKey<T> {
     fn drop(&mut self) {
         unsafe {
-            mprotect(self.ptr, Prot::ReadWrite);
-            ptr::drop_in_place(self.ptr.as_ptr());
+            protect(self.ptr, Prot::ReadWrite);
+
+            // the value was zeroed in a child process, it may not be valid
+            let wiped = self.policy.wipe_on_fork && canary::restore_wiped(self.ptr);
+            self.check_canary();
+            if !wiped {
+                ptr::drop_in_place(self.ptr.as_ptr());
+            }
+
+            self.policy.release(self.ptr.cast(), mem::size_of_val(self.ptr.as_ref()));
+            #[cfg(feature = "stats")]
+            stats::unregister(self.ptr);
             free(self.ptr);
         }
     }
@@ -259,40 +551,117 @@ impl<T: ?Sized> Drop for SecKey<T> {
 
 
 /// Read Guard
-pub struct SecReadGuard<'a, T: 'a + ?Sized>(&'a SecKey<T>);
+pub struct SecReadGuard<'a, T: 'a + ?Sized> {
+    ptr: NonNull<T>,
+    key: &'a (dyn Lock + 'a)
+}
+
+impl<'a, T: 'a + ?Sized> SecReadGuard<'a, T> {
+    /// The caller must have unlocked `key` for reading `ptr`.
+    #[inline]
+    pub(crate) unsafe fn from_raw(ptr: NonNull<T>, key: &'a (dyn Lock + 'a)) -> SecReadGuard<'a, T> {
+        SecReadGuard { ptr, key }
+    }
+
+    /// Make a guard for a part of the protected value.
+    ///
+    /// The whole key stays readable until the new guard is dropped.
+    ///
+    /// ```
+    /// use seckey::{ SecKey, SecReadGuard };
+    ///
+    /// struct Keys {
+    ///     enc_key: [u8; 4],
+    ///     mac_key: [u8; 4]
+    /// }
+    ///
+    /// let keys = SecKey::new(Keys { enc_key: [1; 4], mac_key: [2; 4] })
+    ///     .unwrap_or_else(|_| panic!());
+    /// let mac_key = SecReadGuard::map(keys.read(), |k| &k.mac_key);
+    /// assert_eq!([2; 4], *mac_key);
+    /// ```
+    #[inline]
+    pub fn map<U: 'a + ?Sized, F>(this: SecReadGuard<'a, T>, f: F) -> SecReadGuard<'a, U>
+        where F: FnOnce(&T) -> &U
+    {
+        let ptr = NonNull::from(f(&this));
+        let key = this.key;
+        mem::forget(this);
+
+        SecReadGuard { ptr, key }
+    }
+}
 
 impl<'a, T: 'a + ?Sized> Deref for SecReadGuard<'a, T> {
     type Target = T;
     fn deref(&self) -> &T {
-        unsafe { self.0.ptr.as_ref() }
+        unsafe { self.ptr.as_ref() }
     }
 }
 
 impl<'a, T: 'a + ?Sized> Drop for SecReadGuard<'a, T> {
     fn drop(&mut self) {
-        unsafe { self.0.lock() }
+        unsafe { self.key.lock() }
     }
 }
 
 
 /// Write Guard
-pub struct SecWriteGuard<'a, T: 'a + ?Sized>(&'a mut SecKey<T>);
+pub struct SecWriteGuard<'a, T: 'a + ?Sized> {
+    ptr: NonNull<T>,
+    key: &'a (dyn Lock + 'a),
+    _marker: PhantomData<&'a mut T>
+}
+
+impl<'a, T: 'a + ?Sized> SecWriteGuard<'a, T> {
+    /// The caller must have unlocked `key` for writing `ptr`, and hold it exclusively.
+    #[inline]
+    pub(crate) unsafe fn from_raw(ptr: NonNull<T>, key: &'a (dyn Lock + 'a)) -> SecWriteGuard<'a, T> {
+        SecWriteGuard { ptr, key, _marker: PhantomData }
+    }
+
+    /// Make a guard for a part of the protected value.
+    ///
+    /// The whole key stays writable until the new guard is dropped.
+    ///
+    /// ```
+    /// use seckey::{ SecKey, SecWriteGuard };
+    ///
+    /// let mut keys = SecKey::new(([1u8; 4], [2u8; 4])).unwrap();
+    /// {
+    ///     let mut mac_key = SecWriteGuard::map(keys.write(), |k| &mut k.1);
+    ///     mac_key[0] = 0;
+    /// }
+    /// assert_eq!(([1; 4], [0, 2, 2, 2]), *keys.read());
+    /// ```
+    #[inline]
+    pub fn map<U: 'a + ?Sized, F>(mut this: SecWriteGuard<'a, T>, f: F) -> SecWriteGuard<'a, U>
+        where F: FnOnce(&mut T) -> &mut U
+    {
+        let ptr = NonNull::from(f(&mut this));
+        let key = this.key;
+        mem::forget(this);
+
+        SecWriteGuard { ptr, key, _marker: PhantomData }
+    }
+}
 
 impl<'a, T: 'a + ?Sized> Deref for SecWriteGuard<'a, T> {
     type Target = T;
     fn deref(&self) -> &T {
-        unsafe { self.0.ptr.as_ref() }
+        unsafe { self.ptr.as_ref() }
     }
 }
 
 impl<'a, T: 'a + ?Sized> DerefMut for SecWriteGuard<'a, T> {
     fn deref_mut(&mut self) -> &mut T {
-        unsafe { self.0.ptr.as_mut() }
+        unsafe { self.ptr.as_mut() }
     }
 }
 
 impl<'a, T: 'a + ?Sized> Drop for SecWriteGuard<'a, T> {
     fn drop(&mut self) {
-        unsafe { self.0.lock() }
+        unsafe { self.key.lock() }
     }
 }
+
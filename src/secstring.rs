@@ -0,0 +1,173 @@
+use core::{ fmt, str };
+use ::{ SecKey, SecKeyError, SecReadGuard, SecWriteGuard, SecVec, zero };
+use secvec::SecBuf;
+
+
+/// Secure String
+///
+/// A growable UTF-8 string that never leaves the secure heap,
+/// see [`SecVec`](struct.SecVec.html).
+///
+/// ```
+/// use seckey::SecString;
+///
+/// let mut pass = SecString::new().unwrap();
+/// pass.push_str("hunter");
+/// pass.push('3');
+/// assert_eq!(pass.pop(), Some('3'));
+/// pass.push('2');
+/// assert_eq!(&*pass.read(), "hunter2");
+///
+/// let key = pass.into_seckey();
+/// assert_eq!(&*key.read(), "hunter2");
+/// ```
+pub struct SecString(SecBuf<str>);
+
+impl SecString {
+    #[inline]
+    #[track_caller]
+    pub fn new() -> Result<SecString, SecKeyError> {
+        SecString::with_capacity(0)
+    }
+
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity(cap: usize) -> Result<SecString, SecKeyError> {
+        SecBuf::with_capacity(cap).map(SecString)
+    }
+
+    /// Convert a `SecVec` into a `SecString` without copying.
+    ///
+    /// On failure the `SecVec` is returned untouched.
+    ///
+    /// ```
+    /// use seckey::{ SecVec, SecString };
+    ///
+    /// let mut buf = SecVec::new().unwrap();
+    /// buf.extend_from_slice("💖".as_bytes());
+    /// let s = SecString::from_utf8(buf).unwrap();
+    /// assert_eq!(&*s.read(), "💖");
+    ///
+    /// let mut buf = SecVec::new().unwrap();
+    /// buf.extend_from_slice(&[0xf0, 0x9f, 0x92]);
+    /// assert!(SecString::from_utf8(buf).is_err());
+    /// ```
+    pub fn from_utf8(vec: SecVec) -> Result<SecString, SecVec> {
+        let is_utf8 = str::from_utf8(&vec.read()).is_ok();
+
+        if is_utf8 {
+            Ok(SecString(vec.0.cast()))
+        } else {
+            Err(vec)
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Reserve capacity for at least `additional` more bytes.
+    ///
+    /// Returns an error if the secure allocation fails.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), SecKeyError> {
+        self.0.try_reserve(additional)
+    }
+
+    /// Reserve capacity for at least `additional` more bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the secure allocation fails.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    pub fn push(&mut self, ch: char) {
+        let mut buf = [0; 4];
+        self.push_str(ch.encode_utf8(&mut buf));
+        zero(&mut buf);
+    }
+
+    #[inline]
+    pub fn push_str(&mut self, s: &str) {
+        unsafe { self.0.extend_from_slice(s.as_bytes()) }
+    }
+
+    /// Remove the last char, zeroing its bytes.
+    pub fn pop(&mut self) -> Option<char> {
+        let ch = self.read().chars().next_back()?;
+        let len = self.len() - ch.len_utf8();
+        unsafe { self.0.truncate(len) };
+        Some(ch)
+    }
+
+    /// Shorten the string to `len` bytes, zeroing the removed bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `len` does not lie on a char boundary.
+    pub fn truncate(&mut self, len: usize) {
+        if len >= self.len() {
+            return;
+        }
+
+        assert!(self.read().is_char_boundary(len), "len is not a char boundary");
+        unsafe { self.0.truncate(len) }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        unsafe { self.0.truncate(0) }
+    }
+
+    /// Borrow Read
+    #[inline]
+    pub fn read(&self) -> SecReadGuard<'_, str> {
+        self.0.key().read()
+    }
+
+    /// Borrow Write
+    #[inline]
+    pub fn write(&mut self) -> SecWriteGuard<'_, str> {
+        self.0.key_mut().write()
+    }
+
+    /// Convert into a `SecKey<str>` without copying.
+    #[inline]
+    pub fn into_seckey(self) -> SecKey<str> {
+        self.0.into_seckey()
+    }
+
+    /// Convert into a `SecVec` without copying.
+    #[inline]
+    pub fn into_bytes(self) -> SecVec {
+        SecVec(self.0.cast())
+    }
+}
+
+impl From<SecKey<str>> for SecString {
+    fn from(key: SecKey<str>) -> SecString {
+        SecString(SecBuf::from_seckey(key))
+    }
+}
+
+impl fmt::Debug for SecString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SecString")
+            .field(&format_args!("{:p}", self.0.key()))
+            .finish()
+    }
+}
@@ -0,0 +1,315 @@
+use core::{ cmp, fmt, mem, ptr };
+use core::ptr::NonNull;
+use memsec::memzero;
+use seckey::{ alloc_sized, alloc_sized_with };
+use ::{ SecKey, SecKeyError, SecReadGuard, SecWriteGuard };
+
+
+/// Byte-like unsized types that a `SecBuf` can hold.
+///
+/// # Safety
+///
+/// `from_raw_parts` and `len` must round-trip the byte length.
+pub(crate) unsafe trait RawBytes {
+    fn from_raw_parts(ptr: NonNull<u8>, len: usize) -> NonNull<Self>;
+    fn len(ptr: NonNull<Self>) -> usize;
+}
+
+unsafe impl RawBytes for [u8] {
+    #[inline]
+    fn from_raw_parts(ptr: NonNull<u8>, len: usize) -> NonNull<[u8]> {
+        NonNull::slice_from_raw_parts(ptr, len)
+    }
+
+    #[inline]
+    fn len(ptr: NonNull<[u8]>) -> usize {
+        ptr.len()
+    }
+}
+
+unsafe impl RawBytes for str {
+    #[inline]
+    fn from_raw_parts(ptr: NonNull<u8>, len: usize) -> NonNull<str> {
+        unsafe { NonNull::new_unchecked(<[u8]>::from_raw_parts(ptr, len).as_ptr() as *mut str) }
+    }
+
+    #[inline]
+    fn len(ptr: NonNull<str>) -> usize {
+        (ptr.as_ptr() as *mut [u8]).len()
+    }
+}
+
+
+/// Growable buffer shared by `SecVec` and `SecString`.
+///
+/// `key` covers the `len` initialized bytes, the allocation behind it holds `cap` bytes.
+pub(crate) struct SecBuf<T: ?Sized + RawBytes> {
+    key: SecKey<T>,
+    cap: usize
+}
+
+impl<T: ?Sized + RawBytes> SecBuf<T> {
+    #[track_caller]
+    pub(crate) fn with_capacity(cap: usize) -> Result<SecBuf<T>, SecKeyError> {
+        unsafe {
+            let memptr = alloc_sized(cap)?;
+            let key = SecKey::from_unprotected(T::from_raw_parts(memptr.cast(), 0))?;
+
+            Ok(SecBuf { key, cap })
+        }
+    }
+
+    #[inline]
+    pub(crate) fn from_seckey(key: SecKey<T>) -> SecBuf<T> {
+        let cap = T::len(key.ptr);
+        SecBuf { key, cap }
+    }
+
+    #[inline]
+    pub(crate) fn into_seckey(self) -> SecKey<T> {
+        self.key
+    }
+
+    #[inline]
+    pub(crate) fn len(&self) -> usize {
+        T::len(self.key.ptr)
+    }
+
+    #[inline]
+    pub(crate) fn capacity(&self) -> usize {
+        self.cap
+    }
+
+    #[inline]
+    pub(crate) fn key(&self) -> &SecKey<T> {
+        &self.key
+    }
+
+    #[inline]
+    pub(crate) fn key_mut(&mut self) -> &mut SecKey<T> {
+        &mut self.key
+    }
+
+    #[inline]
+    pub(crate) fn cast<U: ?Sized + RawBytes>(self) -> SecBuf<U> {
+        let len = self.len();
+        let SecBuf { key, cap } = self;
+        let memptr = key.ptr.cast();
+        let policy = key.policy;
+
+        // hand the allocation over without freeing it
+        #[cfg(debug_assertions)]
+        let origin = key.origin.cast::<U>();
+        mem::forget(key);
+
+        #[allow(unused_mut)]
+        let mut key = unsafe { SecKey::from_raw(U::from_raw_parts(memptr, len), policy) };
+        #[cfg(debug_assertions)]
+        { key.origin = origin; }
+
+        SecBuf { key, cap }
+    }
+
+    #[inline]
+    unsafe fn set_len(&mut self, len: usize) {
+        self.key.ptr = T::from_raw_parts(self.key.ptr.cast(), len);
+    }
+
+    fn grow(&mut self, cap: usize) -> Result<(), SecKeyError> {
+        unsafe {
+            let memptr = alloc_sized_with(cap, &self.key.policy)?;
+            let len = self.len();
+
+            // copy secret from old allocation
+            {
+                let _guard = self.key.read();
+                ptr::copy_nonoverlapping(
+                    self.key.ptr.cast::<u8>().as_ptr(),
+                    memptr.cast::<u8>().as_ptr(),
+                    len
+                );
+            }
+
+            // protect secret
+            #[allow(unused_mut)]
+            let mut key = SecKey::from_unprotected_with(T::from_raw_parts(memptr.cast(), len), &self.key.policy)?;
+
+            // report where the buffer was created, not where it grew
+            #[cfg(debug_assertions)]
+            { key.origin = self.key.origin; }
+
+            // old allocation is zeroed by `memsec::free`
+            self.key = key;
+            self.cap = cap;
+
+            Ok(())
+        }
+    }
+
+    pub(crate) fn try_reserve(&mut self, additional: usize) -> Result<(), SecKeyError> {
+        let len = self.len();
+        if self.cap - len >= additional {
+            return Ok(());
+        }
+
+        let cap = len.checked_add(additional).ok_or(SecKeyError::AllocFailed)?;
+        self.grow(cmp::max(cap, self.cap.saturating_mul(2)))
+    }
+
+    pub(crate) fn reserve(&mut self, additional: usize) {
+        if let Err(err) = self.try_reserve(additional) {
+            panic!("{}", err);
+        }
+    }
+
+    /// The caller must keep `T` valid, eg. push only UTF-8 to a `str` buffer.
+    pub(crate) unsafe fn extend_from_slice(&mut self, src: &[u8]) {
+        self.reserve(src.len());
+
+        let len = self.len();
+        let dst = self.key.ptr.cast::<u8>().as_ptr();
+
+        let guard = self.key.write();
+        ptr::copy_nonoverlapping(src.as_ptr(), dst.add(len), src.len());
+        drop(guard);
+
+        self.set_len(len + src.len());
+    }
+
+    /// The caller must keep `T` valid, eg. truncate a `str` buffer only at a char boundary.
+    pub(crate) unsafe fn truncate(&mut self, len: usize) {
+        let old_len = self.len();
+        if len >= old_len {
+            return;
+        }
+
+        let dst = self.key.ptr.cast::<u8>().as_ptr();
+
+        let guard = self.key.write();
+        memzero(dst.add(len), old_len - len);
+        drop(guard);
+
+        self.set_len(len);
+    }
+}
+
+
+/// Secure Vec
+///
+/// A growable byte buffer that never leaves the secure heap.
+/// Growing copies into a new [memsec/malloc](../../memsec/fn.malloc.html) allocation,
+/// the old one is zeroed and freed.
+///
+/// ```
+/// use seckey::SecVec;
+///
+/// let mut buf = SecVec::new().unwrap();
+/// buf.push(1);
+/// buf.extend_from_slice(&[2, 3, 4]);
+/// buf.truncate(3);
+/// assert_eq!(*buf.read(), [1, 2, 3]);
+///
+/// let key = buf.into_seckey();
+/// assert_eq!(*key.read(), [1, 2, 3]);
+/// ```
+pub struct SecVec(pub(crate) SecBuf<[u8]>);
+
+impl SecVec {
+    #[inline]
+    #[track_caller]
+    pub fn new() -> Result<SecVec, SecKeyError> {
+        SecVec::with_capacity(0)
+    }
+
+    #[inline]
+    #[track_caller]
+    pub fn with_capacity(cap: usize) -> Result<SecVec, SecKeyError> {
+        SecBuf::with_capacity(cap).map(SecVec)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.0.capacity()
+    }
+
+    /// Reserve capacity for at least `additional` more bytes.
+    ///
+    /// Returns an error if the secure allocation fails.
+    #[inline]
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), SecKeyError> {
+        self.0.try_reserve(additional)
+    }
+
+    /// Reserve capacity for at least `additional` more bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the secure allocation fails.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional)
+    }
+
+    #[inline]
+    pub fn push(&mut self, byte: u8) {
+        self.extend_from_slice(&[byte]);
+    }
+
+    #[inline]
+    pub fn extend_from_slice(&mut self, src: &[u8]) {
+        unsafe { self.0.extend_from_slice(src) }
+    }
+
+    /// Shorten the buffer to `len` bytes, zeroing the removed bytes.
+    #[inline]
+    pub fn truncate(&mut self, len: usize) {
+        unsafe { self.0.truncate(len) }
+    }
+
+    #[inline]
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    /// Borrow Read
+    #[inline]
+    pub fn read(&self) -> SecReadGuard<'_, [u8]> {
+        self.0.key().read()
+    }
+
+    /// Borrow Write
+    #[inline]
+    pub fn write(&mut self) -> SecWriteGuard<'_, [u8]> {
+        self.0.key_mut().write()
+    }
+
+    /// Convert into a `SecKey<[u8]>` without copying.
+    #[inline]
+    pub fn into_seckey(self) -> SecKey<[u8]> {
+        self.0.into_seckey()
+    }
+}
+
+impl From<SecKey<[u8]>> for SecVec {
+    fn from(key: SecKey<[u8]>) -> SecVec {
+        SecVec(SecBuf::from_seckey(key))
+    }
+}
+
+impl fmt::Debug for SecVec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SecVec")
+            .field(&format_args!("{:p}", self.0.key()))
+            .finish()
+    }
+}
@@ -0,0 +1,238 @@
+use core::{ cmp, fmt, ptr, str };
+use core::marker::PhantomData;
+use std::string::String;
+use std::vec::Vec;
+use serde::{ Serialize, Serializer, Deserialize, Deserializer };
+use serde::de::{ self, Visitor, SeqAccess };
+use ::{ SecKey, SecVec, SecString, zero };
+
+
+/// Cap the preallocation, the size hint comes from untrusted input.
+const MAX_PREALLOC: usize = 4096;
+
+/// Zero a temporary buffer handed over by the deserializer.
+fn zero_vec(mut buf: Vec<u8>) {
+    zero(&mut buf[..]);
+}
+
+fn zero_string(buf: String) {
+    zero_vec(buf.into_bytes());
+}
+
+
+/// Explicitly expose a key to a `Serializer`.
+///
+/// `SecKey` does not implement `Serialize`, so a key is never written out by accident.
+/// Note that the serializer output is not protected.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate seckey;
+///
+/// use seckey::{ SecKey, Exposed };
+///
+/// # fn main() {
+/// let key = SecKey::new([1u8, 2, 3]).unwrap();
+/// let json = serde_json::to_string(&Exposed(&key)).unwrap();
+/// assert_eq!(json, "[1,2,3]");
+/// # }
+/// ```
+pub struct Exposed<'a, T: 'a + ?Sized>(pub &'a SecKey<T>);
+
+impl<'a, T: 'a + ?Sized + Serialize> Serialize for Exposed<'a, T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.read().serialize(serializer)
+    }
+}
+
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = SecKey<[u8]>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("bytes")
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        let mut buf = SecVec::with_capacity(v.len()).map_err(E::custom)?;
+        buf.extend_from_slice(v);
+        Ok(buf.into_seckey())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, mut v: Vec<u8>) -> Result<Self::Value, E> {
+        let result = SecKey::from_bytes(&mut v).map_err(E::custom);
+        zero_vec(v);
+        result
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_string<E: de::Error>(self, v: String) -> Result<Self::Value, E> {
+        self.visit_byte_buf(v.into_bytes())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let cap = cmp::min(seq.size_hint().unwrap_or(0), MAX_PREALLOC);
+        let mut buf = SecVec::with_capacity(cap).map_err(de::Error::custom)?;
+
+        while let Some(byte) = seq.next_element()? {
+            buf.try_reserve(1).map_err(de::Error::custom)?;
+            buf.push(byte);
+        }
+
+        Ok(buf.into_seckey())
+    }
+}
+
+/// Deserialize into the secure heap.
+///
+/// Accepts bytes, strings and sequences of `u8`.
+/// Owned buffers handed over by the deserializer are zeroed,
+/// a sequence is read byte by byte and never leaves the secure heap.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate seckey;
+///
+/// use seckey::SecKey;
+///
+/// # fn main() {
+/// let key: SecKey<[u8]> = serde_json::from_str("[1, 2, 3]").unwrap();
+/// assert_eq!(*key.read(), [1, 2, 3]);
+/// # }
+/// ```
+impl<'de> Deserialize<'de> for SecKey<[u8]> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+
+struct StrVisitor;
+
+impl<'de> Visitor<'de> for StrVisitor {
+    type Value = SecKey<str>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a string")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        let mut buf = SecString::with_capacity(v.len()).map_err(E::custom)?;
+        buf.push_str(v);
+        Ok(buf.into_seckey())
+    }
+
+    fn visit_string<E: de::Error>(self, mut v: String) -> Result<Self::Value, E> {
+        let result = SecKey::from_str(&mut v).map_err(E::custom);
+        zero_string(v);
+        result
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        match str::from_utf8(v) {
+            Ok(s) => self.visit_str(s),
+            Err(_) => Err(E::invalid_value(de::Unexpected::Other("invalid UTF-8"), &self))
+        }
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        match String::from_utf8(v) {
+            Ok(s) => self.visit_string(s),
+            Err(err) => {
+                zero_vec(err.into_bytes());
+                Err(E::invalid_value(de::Unexpected::Other("invalid UTF-8"), &self))
+            }
+        }
+    }
+}
+
+/// Deserialize into the secure heap.
+///
+/// An owned `String` handed over by the deserializer is zeroed.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate seckey;
+///
+/// use seckey::SecKey;
+///
+/// # fn main() {
+/// let key: SecKey<str> = serde_json::from_str("\"hunter2\"").unwrap();
+/// assert_eq!(&*key.read(), "hunter2");
+/// # }
+/// ```
+impl<'de> Deserialize<'de> for SecKey<str> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_string(StrVisitor)
+    }
+}
+
+
+struct ArrayVisitor<const N: usize>(PhantomData<[u8; N]>);
+
+impl<'de, const N: usize> Visitor<'de> for ArrayVisitor<N> {
+    type Value = SecKey<[u8; N]>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "an array of length {}", N)
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        if v.len() != N {
+            return Err(E::invalid_length(v.len(), &self));
+        }
+
+        unsafe {
+            SecKey::with(|memptr: *mut [u8; N]| {
+                ptr::copy_nonoverlapping(v.as_ptr(), memptr as *mut u8, N)
+            })
+        }.map_err(E::custom)
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        let result = self.visit_bytes(&v);
+        zero_vec(v);
+        result
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut key = unsafe { SecKey::with(|memptr: *mut [u8; N]| ptr::write_bytes(memptr, 0, 1)) }
+            .map_err(de::Error::custom)?;
+
+        {
+            let mut buf = key.write();
+            for (i, byte) in buf.iter_mut().enumerate() {
+                *byte = seq.next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+        }
+
+        Ok(key)
+    }
+}
+
+/// Deserialize into the secure heap.
+///
+/// Each byte is written straight into the protected array.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate seckey;
+///
+/// use seckey::SecKey;
+///
+/// # fn main() {
+/// let key: SecKey<[u8; 4]> = serde_json::from_str("[1, 2, 3, 4]").unwrap();
+/// assert_eq!(*key.read(), [1, 2, 3, 4]);
+/// # }
+/// ```
+impl<'de, const N: usize> Deserialize<'de> for SecKey<[u8; N]> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_tuple(N, ArrayVisitor(PhantomData))
+    }
+}
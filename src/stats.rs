@@ -0,0 +1,242 @@
+//! Accounting of live `SecKey` allocations.
+//!
+//! Every `SecKey` holds its own `mlock`ed pages, so forgotten keys add up
+//! against `RLIMIT_MEMLOCK`. With the `stats` feature each allocation is
+//! recorded with its type, and the hook set with `set_limit_hook` is called
+//! once the keys lock more than 90% of the limit.
+//!
+//! ```
+//! use seckey::{ SecKey, stats };
+//!
+//! let _k = SecKey::new([0u8; 32]).unwrap();
+//!
+//! let snapshot = stats::snapshot();
+//! assert!(snapshot.live >= 1);
+//! assert!(snapshot.types.iter().any(|t| t.type_name == "[u8; 32]"));
+//! println!("{}", snapshot);
+//! ```
+
+use core::{ any, fmt, mem };
+use core::ptr::NonNull;
+use std::collections::HashMap;
+use std::sync::{ Mutex, MutexGuard, OnceLock, PoisonError };
+use std::vec::Vec;
+use libc;
+use policy::page_size;
+
+
+/// Report once the live keys lock this share of `RLIMIT_MEMLOCK`, in percent.
+const WARN_PERCENT: u64 = 90;
+
+/// Bytes in front of the value, see `memsec::malloc`.
+const CANARY_SIZE: usize = 16;
+
+/// Guard pages and the page holding the allocation size.
+const EXTRA_PAGES: usize = 3;
+
+struct Entry {
+    type_name: &'static str,
+    bytes: usize,
+    locked: bool
+}
+
+impl Entry {
+    #[inline]
+    fn locked_pages(&self) -> usize {
+        if self.locked { pages(self.bytes) } else { 0 }
+    }
+}
+
+#[derive(Default)]
+struct Registry {
+    entries: HashMap<usize, Entry>,
+    locked_pages: usize,
+    warned: bool
+}
+
+static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+
+static LIMIT_HOOK: Mutex<Option<fn(&Snapshot)>> = Mutex::new(None);
+
+fn registry() -> MutexGuard<'static, Registry> {
+    REGISTRY.get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Pages `memsec` unprotects for a value of `bytes`, and locks unless
+/// the policy turns `lock_memory` off.
+#[inline]
+fn pages(bytes: usize) -> usize {
+    (bytes + CANARY_SIZE).div_ceil(page_size())
+}
+
+/// The current `RLIMIT_MEMLOCK` in bytes, `None` if unlimited or unknown.
+#[cfg(unix)]
+pub fn memlock_limit() -> Option<u64> {
+    unsafe {
+        let mut rlim: libc::rlimit = mem::zeroed();
+        if libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut rlim) != 0 || rlim.rlim_cur == libc::RLIM_INFINITY {
+            None
+        } else {
+            Some(rlim.rlim_cur as u64)
+        }
+    }
+}
+
+/// The current `RLIMIT_MEMLOCK` in bytes, `None` if unlimited or unknown.
+#[cfg(not(unix))]
+pub fn memlock_limit() -> Option<u64> {
+    None
+}
+
+#[inline]
+fn addr<T: ?Sized>(ptr: NonNull<T>) -> usize {
+    ptr.cast::<u8>().as_ptr() as usize
+}
+
+/// Call `hook` once the live keys lock more than 90% of `RLIMIT_MEMLOCK`.
+///
+/// It is called again only after the keys dropped below that share.
+/// Replaces the previous hook, nothing is reported without one.
+///
+/// ```
+/// use seckey::stats;
+///
+/// stats::set_limit_hook(|snapshot| eprintln!("seckey: close to RLIMIT_MEMLOCK\n{}", snapshot));
+/// ```
+pub fn set_limit_hook(hook: fn(&Snapshot)) {
+    *LIMIT_HOOK.lock().unwrap_or_else(PoisonError::into_inner) = Some(hook);
+}
+
+/// Record a new allocation of `bytes`, `locked` with `mlock`.
+pub(crate) fn register<T: ?Sized>(ptr: NonNull<T>, bytes: usize, locked: bool) {
+    let limit = memlock_limit();
+
+    let report = {
+        let mut registry = registry();
+        let entry = Entry { type_name: any::type_name::<T>(), bytes, locked };
+        registry.locked_pages += entry.locked_pages();
+        if let Some(old) = registry.entries.insert(addr(ptr), entry) {
+            registry.locked_pages -= old.locked_pages();
+        }
+
+        let locked = (registry.locked_pages * page_size()) as u64;
+        match limit {
+            Some(limit) if locked * 100 >= limit * WARN_PERCENT => !mem::replace(&mut registry.warned, true),
+            _ => {
+                registry.warned = false;
+                false
+            }
+        }
+    };
+
+    if report {
+        let hook = *LIMIT_HOOK.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(hook) = hook {
+            hook(&snapshot());
+        }
+    }
+}
+
+/// Record the type an allocation is used as.
+pub(crate) fn retype<T: ?Sized>(ptr: NonNull<T>) {
+    if let Some(entry) = registry().entries.get_mut(&addr(ptr)) {
+        entry.type_name = any::type_name::<T>();
+    }
+}
+
+/// Forget an allocation before it is freed.
+pub(crate) fn unregister<T: ?Sized>(ptr: NonNull<T>) {
+    let mut registry = registry();
+    if let Some(entry) = registry.entries.remove(&addr(ptr)) {
+        registry.locked_pages -= entry.locked_pages();
+    }
+}
+
+
+/// Live keys of one type.
+#[derive(Debug, Clone)]
+pub struct TypeStats {
+    pub type_name: &'static str,
+    pub count: usize,
+    /// Bytes requested, including unused `SecVec` capacity.
+    pub bytes: usize,
+    pub locked_pages: usize
+}
+
+/// Live keys at one point in time.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub live: usize,
+    pub bytes: usize,
+    pub locked_pages: usize,
+    /// Pages of all keys, locked or not, plus guard pages.
+    pub mapped_pages: usize,
+    pub page_size: usize,
+    pub memlock_limit: Option<u64>,
+    /// Sorted by locked pages, largest first.
+    pub types: Vec<TypeStats>
+}
+
+impl Snapshot {
+    #[inline]
+    pub fn locked_bytes(&self) -> u64 {
+        (self.locked_pages * self.page_size) as u64
+    }
+
+    /// The live keys lock more than 90% of `RLIMIT_MEMLOCK`.
+    #[inline]
+    pub fn near_memlock_limit(&self) -> bool {
+        self.memlock_limit
+            .map(|limit| self.locked_bytes() * 100 >= limit * WARN_PERCENT)
+            .unwrap_or(false)
+    }
+}
+
+/// Collect the live keys, grouped by type.
+pub fn snapshot() -> Snapshot {
+    let mut types: HashMap<&'static str, TypeStats> = HashMap::new();
+    let (live, locked_pages, data_pages) = {
+        let registry = registry();
+        let mut data_pages = 0;
+        for entry in registry.entries.values() {
+            let stats = types.entry(entry.type_name)
+                .or_insert(TypeStats { type_name: entry.type_name, count: 0, bytes: 0, locked_pages: 0 });
+            stats.count += 1;
+            stats.bytes += entry.bytes;
+            stats.locked_pages += entry.locked_pages();
+            data_pages += pages(entry.bytes);
+        }
+        (registry.entries.len(), registry.locked_pages, data_pages)
+    };
+
+    let mut types = types.into_values().collect::<Vec<_>>();
+    types.sort_by(|a, b| b.locked_pages.cmp(&a.locked_pages).then(a.type_name.cmp(b.type_name)));
+
+    Snapshot {
+        live,
+        bytes: types.iter().map(|t| t.bytes).sum(),
+        locked_pages,
+        mapped_pages: data_pages + live * EXTRA_PAGES,
+        page_size: page_size(),
+        memlock_limit: memlock_limit(),
+        types
+    }
+}
+
+impl fmt::Display for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} live keys, {} bytes, {} locked pages ({} bytes", self.live, self.bytes, self.locked_pages, self.locked_bytes())?;
+        match self.memlock_limit {
+            Some(limit) => writeln!(f, " of {} RLIMIT_MEMLOCK)", limit)?,
+            None => writeln!(f, ")")?
+        }
+
+        for stats in &self.types {
+            writeln!(f, "  {:>8} keys {:>10} bytes {:>8} pages  {}", stats.count, stats.bytes, stats.locked_pages, stats.type_name)?;
+        }
+
+        Ok(())
+    }
+}
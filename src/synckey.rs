@@ -0,0 +1,380 @@
+use core::fmt;
+use core::ops::{ Deref, DerefMut };
+use core::sync::atomic::{ AtomicUsize, Ordering };
+use core::{ mem, ptr };
+use std::sync::{ Arc, Condvar, Mutex, PoisonError };
+use memsec::Prot;
+use policy::{ self, protect };
+use ::SecKey;
+
+
+/// Thread-safe Secure Key
+///
+/// Same as [`SecKey`](struct.SecKey.html), but the guard count is atomic,
+/// so one key can be read from many threads at the same time.
+/// Readers share the unprotected region, a writer excludes all readers.
+///
+/// ```
+/// use std::sync::Arc;
+/// use std::thread;
+/// use seckey::SyncSecKey;
+///
+/// let key = Arc::new(SyncSecKey::new([8u8; 8]).unwrap());
+///
+/// let handles = (0..4)
+///     .map(|_| {
+///         let key = key.clone();
+///         thread::spawn(move || assert_eq!([8u8; 8], *key.read()))
+///     })
+///     .collect::<Vec<_>>();
+///
+/// for handle in handles {
+///     handle.join().unwrap();
+/// }
+/// ```
+pub struct SyncSecKey<T: ?Sized> {
+    count: AtomicUsize,
+    transition: Mutex<()>,
+    gate: Gate,
+    key: SecKey<T>
+}
+
+unsafe impl<T: ?Sized + Send> Send for SyncSecKey<T> {}
+unsafe impl<T: ?Sized + Send + Sync> Sync for SyncSecKey<T> {}
+
+impl<T> SyncSecKey<T> {
+    /// ```
+    /// use seckey::SyncSecKey;
+    ///
+    /// let k = SyncSecKey::new([1, 2, 3]).unwrap();
+    /// assert_eq!([1, 2, 3], *k.read());
+    /// ```
+    #[track_caller]
+    pub fn new(t: T) -> Result<SyncSecKey<T>, T> {
+        SecKey::new(t).map(SyncSecKey::from)
+    }
+}
+
+impl<T: ?Sized> From<SecKey<T>> for SyncSecKey<T> {
+    /// ```
+    /// use seckey::{ SecKey, SyncSecKey };
+    ///
+    /// let mut unprotected = [1u8; 2];
+    /// let k = SecKey::from_bytes(&mut unprotected[..]).unwrap();
+    /// let k = SyncSecKey::from(k);
+    /// assert_eq!(*k.read(), [1u8; 2]);
+    /// ```
+    fn from(key: SecKey<T>) -> SyncSecKey<T> {
+        SyncSecKey {
+            count: AtomicUsize::new(0),
+            transition: Mutex::new(()),
+            gate: Gate::new(),
+            key
+        }
+    }
+}
+
+impl<T: ?Sized> SyncSecKey<T> {
+    fn unlock(&self) {
+        // fast path, the region is already readable
+        let mut count = self.count.load(Ordering::Acquire);
+        while count != 0 {
+            match self.count.compare_exchange_weak(count, count + 1, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return,
+                Err(c) => count = c
+            }
+        }
+
+        // slow path, the count only leaves zero after `mprotect`
+        let _transition = self.transition.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.count.load(Ordering::Acquire) == 0 && !policy::covers(self.key.policy.idle, Prot::ReadOnly) {
+            unsafe { protect(self.key.ptr, Prot::ReadOnly) };
+        }
+        self.count.fetch_add(1, Ordering::AcqRel);
+    }
+
+    fn lock(&self) {
+        // the region is readable while we hold a guard
+        unsafe { self.key.check_canary() };
+
+        // fast path, other readers still hold the region
+        let mut count = self.count.load(Ordering::Acquire);
+        while count > 1 {
+            match self.count.compare_exchange_weak(count, count - 1, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => return,
+                Err(c) => count = c
+            }
+        }
+
+        // slow path, the count only leaves zero after `mprotect`
+        let _transition = self.transition.lock().unwrap_or_else(PoisonError::into_inner);
+        if self.count.fetch_sub(1, Ordering::AcqRel) == 1 && !policy::covers(self.key.policy.idle, Prot::ReadOnly) {
+            unsafe { protect(self.key.ptr, self.key.policy.idle) };
+        }
+    }
+
+    /// Borrow Read
+    ///
+    /// Blocks while a writer holds the key.
+    ///
+    /// ```
+    /// use seckey::SyncSecKey;
+    ///
+    /// let secpass = SyncSecKey::new([8u8; 8]).unwrap();
+    /// let rpass1 = secpass.read();
+    /// let rpass2 = secpass.read();
+    /// assert_eq!(*rpass1, *rpass2);
+    /// ```
+    pub fn read(&self) -> SyncReadGuard<'_, T> {
+        self.gate.read();
+        self.unlock();
+        unsafe { self.key.forbid_wiped() };
+
+        SyncReadGuard { key: self }
+    }
+
+    /// Borrow Write
+    ///
+    /// Blocks until all readers and writers release the key.
+    ///
+    /// ```
+    /// use seckey::SyncSecKey;
+    ///
+    /// let secpass = SyncSecKey::new([8u8; 8]).unwrap();
+    /// secpass.write()[0] = 0;
+    /// assert_eq!([0, 8, 8, 8, 8, 8, 8, 8], *secpass.read());
+    /// ```
+    pub fn write(&self) -> SyncWriteGuard<'_, T> {
+        self.unlock_write();
+
+        SyncWriteGuard { key: self }
+    }
+
+    /// Owned Read
+    ///
+    /// Same as `read`, but the guard holds an `Arc` instead of a borrow,
+    /// so it can be stored next to the key or moved to another thread.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use seckey::SyncSecKey;
+    ///
+    /// let secpass = Arc::new(SyncSecKey::new([8u8; 8]).unwrap());
+    /// let rpass = secpass.read_owned();
+    /// drop(secpass);
+    ///
+    /// thread::spawn(move || assert_eq!([8u8; 8], *rpass))
+    ///     .join()
+    ///     .unwrap();
+    /// ```
+    pub fn read_owned(self: &Arc<Self>) -> SecOwnedReadGuard<T> {
+        self.gate.read();
+        self.unlock();
+        unsafe { self.key.forbid_wiped() };
+
+        SecOwnedReadGuard(self.clone())
+    }
+
+    /// Owned Write
+    ///
+    /// Same as `write`, but the guard holds an `Arc` instead of a borrow,
+    /// so it can be moved to another thread. Blocks while any other guard is alive.
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::thread;
+    /// use seckey::{ SyncSecKey, SecOwnedWriteGuard };
+    ///
+    /// let secpass = Arc::new(SyncSecKey::new([8u8; 8]).unwrap());
+    /// let mut wpass = secpass.clone().write_owned();
+    ///
+    /// let wpass = thread::spawn(move || {
+    ///     wpass[0] = 0;
+    ///     wpass
+    /// }).join().unwrap();
+    ///
+    /// let secpass2 = SecOwnedWriteGuard::into_arc(wpass);
+    /// assert_eq!([0, 8, 8, 8, 8, 8, 8, 8], *secpass2.read());
+    /// assert_eq!([0, 8, 8, 8, 8, 8, 8, 8], *secpass.read());
+    /// ```
+    pub fn write_owned(self: Arc<Self>) -> SecOwnedWriteGuard<T> {
+        self.unlock_write();
+        SecOwnedWriteGuard(self)
+    }
+
+    fn unlock_write(&self) {
+        self.gate.write();
+        if !policy::covers(self.key.policy.idle, Prot::ReadWrite) {
+            unsafe { protect(self.key.ptr, Prot::ReadWrite) };
+        }
+        unsafe { self.key.forbid_wiped() };
+    }
+
+    fn lock_write(&self) {
+        unsafe {
+            self.key.check_canary();
+            if !policy::covers(self.key.policy.idle, Prot::ReadWrite) {
+                protect(self.key.ptr, self.key.policy.idle);
+            }
+        }
+        self.gate.release();
+    }
+
+    fn lock_read(&self) {
+        self.lock();
+        self.gate.release();
+    }
+}
+
+impl<T: ?Sized> fmt::Debug for SyncSecKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("SyncSecKey")
+            .field(&format_args!("{:p}", self.key.ptr))
+            .field(&self.count)
+            .finish()
+    }
+}
+
+impl<T: ?Sized> fmt::Pointer for SyncSecKey<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:p}", self.key.ptr)
+    }
+}
+
+
+/// A readers-writer lock without a guard,
+/// so an owned guard can release it from any thread.
+struct Gate {
+    /// Number of readers, or `WRITER`.
+    state: Mutex<usize>,
+    cond: Condvar
+}
+
+const WRITER: usize = usize::MAX;
+
+impl Gate {
+    fn new() -> Gate {
+        Gate { state: Mutex::new(0), cond: Condvar::new() }
+    }
+
+    fn read(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        while *state == WRITER {
+            state = self.cond.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+        *state += 1;
+    }
+
+    fn write(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        while *state != 0 {
+            state = self.cond.wait(state).unwrap_or_else(PoisonError::into_inner);
+        }
+        *state = WRITER;
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        *state = if *state == WRITER { 0 } else { *state - 1 };
+        if *state == 0 {
+            self.cond.notify_all();
+        }
+    }
+}
+
+
+/// Sync Read Guard
+pub struct SyncReadGuard<'a, T: 'a + ?Sized> {
+    key: &'a SyncSecKey<T>
+}
+
+impl<'a, T: 'a + ?Sized> Deref for SyncReadGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { self.key.key.ptr.as_ref() }
+    }
+}
+
+impl<'a, T: 'a + ?Sized> Drop for SyncReadGuard<'a, T> {
+    fn drop(&mut self) {
+        self.key.lock_read()
+    }
+}
+
+
+/// Sync Write Guard
+pub struct SyncWriteGuard<'a, T: 'a + ?Sized> {
+    key: &'a SyncSecKey<T>
+}
+
+impl<'a, T: 'a + ?Sized> Deref for SyncWriteGuard<'a, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { self.key.key.ptr.as_ref() }
+    }
+}
+
+impl<'a, T: 'a + ?Sized> DerefMut for SyncWriteGuard<'a, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.key.key.ptr.as_ptr() }
+    }
+}
+
+impl<'a, T: 'a + ?Sized> Drop for SyncWriteGuard<'a, T> {
+    fn drop(&mut self) {
+        self.key.lock_write()
+    }
+}
+
+
+/// Owned Read Guard
+pub struct SecOwnedReadGuard<T: ?Sized>(Arc<SyncSecKey<T>>);
+
+impl<T: ?Sized> Deref for SecOwnedReadGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { self.0.key.ptr.as_ref() }
+    }
+}
+
+impl<T: ?Sized> Drop for SecOwnedReadGuard<T> {
+    fn drop(&mut self) {
+        self.0.lock_read()
+    }
+}
+
+
+/// Owned Write Guard
+pub struct SecOwnedWriteGuard<T: ?Sized>(Arc<SyncSecKey<T>>);
+
+impl<T: ?Sized> SecOwnedWriteGuard<T> {
+    /// Protect the key again and give back the `Arc`.
+    #[inline]
+    pub fn into_arc(this: SecOwnedWriteGuard<T>) -> Arc<SyncSecKey<T>> {
+        let key = unsafe { ptr::read(&this.0) };
+        mem::forget(this);
+        key.lock_write();
+        key
+    }
+}
+
+impl<T: ?Sized> Deref for SecOwnedWriteGuard<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { self.0.key.ptr.as_ref() }
+    }
+}
+
+impl<T: ?Sized> DerefMut for SecOwnedWriteGuard<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // the gate excludes every other guard while this one lives
+        unsafe { &mut *self.0.key.ptr.as_ptr() }
+    }
+}
+
+impl<T: ?Sized> Drop for SecOwnedWriteGuard<T> {
+    fn drop(&mut self) {
+        self.0.lock_write()
+    }
+}
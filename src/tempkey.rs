@@ -24,6 +24,9 @@ pub struct TempKey<'a, T: ?Sized + 'static>(&'a mut T);
 
 
 impl<'a, T: ?Sized> TempKey<'a, T> {
+    /// # Safety
+    ///
+    /// `T` must remain valid when all of its bytes are zeroed.
     pub unsafe fn unsafe_from(t: &'a mut T) -> TempKey<'a, T> {
         #[cfg(feature = "use_std")]
         mlock(t as *mut T as *mut u8, mem::size_of_val(t));
@@ -1,7 +1,14 @@
 use core::mem;
+use core::num::Wrapping;
 use memsec::memzero;
 
 
+/// Types whose all-zero bit pattern is a valid value.
+///
+/// # Safety
+///
+/// Implementors must not contain references, pointers or any other field
+/// for which zeroed memory is an invalid value.
 pub unsafe trait ZeroSafe {}
 
 /// Zero a value
@@ -16,11 +23,20 @@ pub unsafe trait ZeroSafe {}
 /// let mut v = &mut [1u8, 2, 3][..];
 /// zero(v);
 /// assert_eq!(v, [0, 0, 0]);
+///
+/// let mut v = ([1u32; 100], 2.0f64);
+/// zero(&mut v);
+/// assert_eq!(v, ([0; 100], 0.0));
 /// ```
 pub fn zero<T: ?Sized + ZeroSafe>(t: &mut T) {
     unsafe { unsafe_zero(t) }
 }
 
+/// Zero a value without checking `ZeroSafe`
+///
+/// # Safety
+///
+/// The value must remain valid when all of its bytes are zeroed.
 pub unsafe fn unsafe_zero<T: ?Sized>(t: &mut T) {
     memzero(t as *mut T as *mut u8, mem::size_of_val(t));
 }
@@ -36,9 +52,9 @@ macro_rules! impl_zerosafe {
             unsafe impl<T: ZeroSafe> ZeroSafe for $t {}
         )*
     };
-    ( Array : $( $n:expr ),* ) => {
+    ( Tuple : $( ( $( $name:ident ),+ ) ),* ) => {
         $(
-            unsafe impl<T: ZeroSafe> ZeroSafe for [T; $n] {}
+            unsafe impl<$( $name: ZeroSafe ),+> ZeroSafe for ( $( $name, )+ ) {}
         )*
     }
 }
@@ -46,18 +62,26 @@ macro_rules! impl_zerosafe {
 impl_zerosafe!{ Type:
     usize, u8, u16, u32, u64, u128,
     isize, i8, i16, i32, i64, i128,
+    f32, f64,
 
     char, str
 }
 
-impl_zerosafe!{ Generic: [T] }
+impl_zerosafe!{ Generic: [T], Wrapping<T> }
 
-impl_zerosafe!{ Array:
-     0,  1,  2,  3,  4,  5,  6,  7,  8,  9, 10, 11, 12, 13, 14, 15,
-    16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31,
-    32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47,
-    48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63,
-    64,
+unsafe impl<T: ZeroSafe, const N: usize> ZeroSafe for [T; N] {}
 
-    128, 256, 384, 512, 1024, 2048
+impl_zerosafe!{ Tuple:
+    (A),
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H),
+    (A, B, C, D, E, F, G, H, I),
+    (A, B, C, D, E, F, G, H, I, J),
+    (A, B, C, D, E, F, G, H, I, J, K),
+    (A, B, C, D, E, F, G, H, I, J, K, L)
 }
@@ -0,0 +1,45 @@
+#![cfg(all(feature = "use_std", debug_assertions))]
+
+extern crate seckey;
+#[cfg(unix)] extern crate libc;
+
+mod common;
+
+use common::{ run_child, is_child };
+use seckey::SecKey;
+
+
+#[test]
+fn canary_underflow_child() {
+    if !is_child() {
+        return;
+    }
+
+    let (mut k, line) = (SecKey::new([0u8; 8]).unwrap(), line!());
+    println!("created at: {}:{}:", file!(), line);
+    let mut wk = k.write();
+
+    // write one byte in front of the value, like an off-by-one in FFI code
+    unsafe {
+        let p = wk.as_mut_ptr().sub(1);
+        *p = !*p;
+    }
+
+    drop(wk);
+    unreachable!();
+}
+
+#[test]
+fn canary_underflow_test() {
+    let output = run_child("canary_underflow_child");
+
+    // the child prints where it created the key
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let created = stdout.find("created at: ").and_then(|at| stdout[at..].lines().next()).unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("canary check failed"), "{}", stderr);
+    assert!(stderr.contains("[u8; 8]"), "{}", stderr);
+    assert!(stderr.contains(created), "{}", stderr);
+}
@@ -0,0 +1,71 @@
+//! Helpers shared by the integration tests.
+
+#![allow(dead_code)]
+
+use std::env;
+use std::process::{ Command, Output };
+#[cfg(unix)] use libc;
+
+
+const CHILD: &str = "SECKEY_TEST_CHILD";
+
+/// Run the test `name` again, in a new process of this test binary.
+pub fn run_child(name: &str) -> Output {
+    Command::new(env::current_exe().unwrap())
+        .args(["--exact", name, "--nocapture"])
+        .env(CHILD, "1")
+        .output()
+        .unwrap()
+}
+
+/// The test runs in a process started by `run_child`.
+///
+/// Tests that are only meant to crash there return early otherwise.
+pub fn is_child() -> bool {
+    env::var_os(CHILD).is_some()
+}
+
+/// Run `f` in a child process, and return its wait status.
+///
+/// The child exits with `0` if `f` returns `true`, `1` otherwise.
+#[cfg(unix)]
+pub fn in_child<F: FnOnce() -> bool>(f: F) -> libc::c_int {
+    wait_child(spawn_child(f))
+}
+
+/// Start `f` in a child process, and return its pid.
+#[cfg(unix)]
+pub fn spawn_child<F: FnOnce() -> bool>(f: F) -> libc::pid_t {
+    unsafe {
+        match libc::fork() {
+            -1 => panic!("fork failed"),
+            0 => libc::_exit(if f() { 0 } else { 1 }),
+            pid => pid
+        }
+    }
+}
+
+/// Wait for a child started with `spawn_child`, and return its wait status.
+#[cfg(unix)]
+pub fn wait_child(pid: libc::pid_t) -> libc::c_int {
+    let mut status = 0;
+    assert_eq!(pid, unsafe { libc::waitpid(pid, &mut status, 0) });
+    status
+}
+
+#[cfg(unix)]
+pub fn assert_exit_ok(status: libc::c_int) {
+    assert!(libc::WIFEXITED(status) && libc::WEXITSTATUS(status) == 0, "status {:#x}", status);
+}
+
+#[cfg(unix)]
+pub fn assert_abort(status: libc::c_int) {
+    assert!(libc::WIFSIGNALED(status) && libc::WTERMSIG(status) == libc::SIGABRT, "status {:#x}", status);
+}
+
+/// The child faulted on protected memory.
+#[cfg(unix)]
+pub fn assert_fault(status: libc::c_int) {
+    let signal = if libc::WIFSIGNALED(status) { libc::WTERMSIG(status) } else { 0 };
+    assert!(signal == libc::SIGSEGV || signal == libc::SIGBUS, "status {:#x}", status);
+}
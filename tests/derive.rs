@@ -0,0 +1,58 @@
+#![cfg(feature = "derive")]
+
+extern crate seckey;
+
+use seckey::{ ZeroSafe, TempKey, zero };
+
+
+#[derive(ZeroSafe)]
+struct Keys {
+    enc_key: [u8; 32],
+    mac_key: [u8; 32],
+    counter: u64
+}
+
+#[derive(ZeroSafe)]
+struct Nonce(u32, [u8; 8]);
+
+#[derive(ZeroSafe)]
+struct Wrap<T>(T);
+
+#[derive(Debug, PartialEq, ZeroSafe)]
+#[repr(u8)]
+enum Mode {
+    Encrypt = 1,
+    Decrypt = 2,
+    Idle = 0
+}
+
+#[test]
+fn derive_zero_struct() {
+    let mut keys = Keys { enc_key: [1; 32], mac_key: [2; 32], counter: 3 };
+    zero(&mut keys);
+    assert_eq!(keys.enc_key, [0; 32]);
+    assert_eq!(keys.mac_key, [0; 32]);
+    assert_eq!(keys.counter, 0);
+
+    let mut nonce = Nonce(1, [2; 8]);
+    zero(&mut nonce);
+    assert_eq!(nonce.0, 0);
+    assert_eq!(nonce.1, [0; 8]);
+}
+
+#[test]
+fn derive_zero_generic() {
+    let mut wrap = Wrap(Nonce(1, [2; 8]));
+    {
+        let key = TempKey::from(&mut wrap);
+        assert_eq!((key.0).0, 1);
+    }
+    assert_eq!((wrap.0).0, 0);
+}
+
+#[test]
+fn derive_zero_enum() {
+    let mut mode = [Mode::Encrypt, Mode::Decrypt];
+    zero(&mut mode);
+    assert_eq!(mode, [Mode::Idle, Mode::Idle]);
+}
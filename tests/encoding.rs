@@ -0,0 +1,71 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+
+use seckey::{ SecKey, SecKeyError, TempKey };
+
+
+#[test]
+fn encoding_hex_test() {
+    for b in 0..=255u8 {
+        let mut buf = [0u8; 2];
+        let k = SecKey::new([b]).unwrap();
+        let mut out = TempKey::from(&mut buf[..]);
+        let mut hex = k.read().encode_hex(&mut out).to_string();
+        assert_eq!(hex, format!("{:02x}", b));
+
+        let mut upper = hex.to_uppercase();
+        assert_eq!(*SecKey::from_hex(&mut hex).unwrap().read(), [b]);
+        assert_eq!(*SecKey::from_hex(&mut upper).unwrap().read(), [b]);
+    }
+
+    for c in 0..=255u8 {
+        let valid = (c as char).is_ascii_hexdigit();
+        let mut src = String::from_utf8_lossy(&[b'0', c]).into_owned();
+        let orig = src.clone();
+        assert_eq!(SecKey::from_hex(&mut src).is_ok(), valid, "{:?}", c as char);
+        if !valid {
+            assert_eq!(src, orig);
+        }
+    }
+
+    assert_eq!(SecKey::from_hex(&mut "abc".to_string()).err(), Some(SecKeyError::InvalidEncoding));
+    assert!(SecKey::from_hex(&mut String::new()).unwrap().read().is_empty());
+}
+
+#[test]
+fn encoding_base64_test() {
+    let vectors = [
+        ("", ""),
+        ("f", "Zg=="),
+        ("fo", "Zm8="),
+        ("foo", "Zm9v"),
+        ("foob", "Zm9vYg=="),
+        ("fooba", "Zm9vYmE="),
+        ("foobar", "Zm9vYmFy")
+    ];
+
+    for &(plain, encoded) in &vectors {
+        let mut src = encoded.to_string();
+        let k = SecKey::from_base64(&mut src).unwrap();
+        assert_eq!(&*k.read(), plain.as_bytes());
+        assert!(src.bytes().all(|b| b == 0));
+
+        let mut buf = [0u8; 8];
+        let mut out = TempKey::from(&mut buf[..]);
+        assert_eq!(k.read().encode_base64(&mut out), encoded);
+    }
+
+    let bytes = (0..=255u8).collect::<Vec<_>>();
+    let k = SecKey::from_bytes(&mut bytes.clone()).unwrap();
+    let mut buf = [0u8; 344];
+    let mut out = TempKey::from(&mut buf[..]);
+    let mut encoded = k.read().encode_base64(&mut out).to_string();
+    assert_eq!(&*SecKey::from_base64(&mut encoded).unwrap().read(), &bytes[..]);
+
+    for invalid in &["Zg=", "Z===", "Zh==", "Zm9", "Zm-v", "Zm9v\n", "=Zm9"] {
+        let mut src = invalid.to_string();
+        assert_eq!(SecKey::from_base64(&mut src).err(), Some(SecKeyError::InvalidEncoding), "{:?}", invalid);
+        assert_eq!(&src, invalid);
+    }
+}
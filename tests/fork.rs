@@ -0,0 +1,106 @@
+#![cfg(all(feature = "use_std", unix))]
+
+extern crate seckey;
+extern crate libc;
+
+mod common;
+
+use common::{ in_child, assert_exit_ok, assert_abort };
+use seckey::{ SecKey, SecVec, SecPool, SealedKey, SyncSecKey, Policy, Idle };
+
+
+#[test]
+fn fork_wipe_test() {
+    let wiped = SecKey::new([7u8; 32]).unwrap();
+    let mut vec = SecVec::new().unwrap();
+    vec.extend_from_slice(&[7; 100]);
+    let pool = SecPool::new(32).unwrap();
+    let mut pooled = pool.alloc([7u8; 32]).unwrap();
+    let sealed = SealedKey::new([7u8; 32]).unwrap();
+    let sync = SyncSecKey::new([7u8; 32]).unwrap();
+
+    // a zeroed value may not be valid, any access aborts
+    assert_abort(in_child(|| *wiped.read() == [7; 32]));
+    assert_abort(in_child(|| vec.read().is_empty()));
+    assert_abort(in_child(|| *pooled.read() == [7; 32]));
+    assert_abort(in_child(|| { pooled.write()[0] = 0; true }));
+    assert_abort(in_child(|| *sealed.read() == [7; 32]));
+    assert_abort(in_child(|| *sync.read() == [7; 32]));
+    assert_abort(in_child(|| { sync.write()[0] = 0; true }));
+
+    // the parent keeps its keys
+    assert_eq!([7; 32], *wiped.read());
+    assert_eq!([7; 100], *vec.read());
+    assert_eq!([7; 32], *pooled.read());
+    assert_eq!([7; 32], *sealed.read());
+    assert_eq!([7; 32], *sync.read());
+}
+
+#[test]
+fn fork_inherit_test() {
+    let inherited = Policy::new().wipe_on_fork(false).build([7u8; 32]).unwrap();
+    let sealed = SealedKey::from(Policy::new().wipe_on_fork(false).build([7u8; 32]).unwrap());
+
+    // the pre-key is inherited too, so the sealed key still opens
+    assert_exit_ok(in_child(|| *inherited.read() == [7; 32] && *sealed.read() == [7; 32]));
+}
+
+#[test]
+fn fork_drop_test() {
+    let wiped = SecKey::new(vec![7u8; 32]).unwrap();
+    let sealed = SealedKey::new(vec![7u8; 32]).unwrap();
+
+    // a zeroed `Vec` is not valid, its destructor must not run
+    assert_exit_ok(in_child(move || {
+        drop(wiped);
+        drop(sealed);
+        true
+    }));
+}
+
+#[test]
+fn fork_reuse_test() {
+    let key = SecKey::new([7u8; 64]).unwrap();
+    let page = key.read().as_ptr() as usize & !4095;
+    drop(key);
+
+    // the global allocator hands the freed key page out again
+    let mut vecs = Vec::new();
+    let reused = loop {
+        let vec = vec![7u8; 64];
+        let addr = vec.as_ptr() as usize;
+        if addr & !4095 == page {
+            break vec;
+        }
+        vecs.push(vec);
+        assert!(vecs.len() < 100_000, "the freed page was not reused");
+    };
+
+    assert_exit_ok(in_child(|| reused == [7; 64]));
+}
+
+#[test]
+fn fork_sealed_nonce_test() {
+    let key = SealedKey::from(Policy::new().wipe_on_fork(false).idle(Idle::ReadOnly).build([7u8; 64]).unwrap());
+    let addr = usize::from_str_radix(format!("{:p}", key).trim_start_matches("0x"), 16).unwrap();
+    let sealed = || unsafe { *(addr as *const [u8; 64]) };
+
+    let mut fds = [0; 2];
+    assert_eq!(0, unsafe { libc::pipe(fds.as_mut_ptr()) });
+
+    // parent and child both seal again, under the same pre-key
+    assert_exit_ok(in_child(|| {
+        drop(key.read());
+        let child = sealed();
+        unsafe { libc::write(fds[1], child.as_ptr() as *const libc::c_void, 64) == 64 }
+    }));
+    drop(key.read());
+
+    let mut child = [0u8; 64];
+    unsafe {
+        assert_eq!(64, libc::read(fds[0], child.as_mut_ptr() as *mut libc::c_void, 64));
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+    assert_ne!(child, sealed());
+}
@@ -0,0 +1,75 @@
+#![cfg(all(feature = "use_std", target_os = "linux"))]
+
+extern crate seckey;
+extern crate libc;
+
+mod common;
+
+use std::fs;
+use common::{ in_child, assert_exit_ok };
+use seckey::{ SecVec, Policy, harden_process };
+
+
+/// `VmFlags` of the mapping that holds `addr`.
+fn vm_flags(addr: usize) -> String {
+    let smaps = fs::read_to_string("/proc/self/smaps").unwrap();
+    let mut inside = false;
+
+    for line in smaps.lines() {
+        if let Some(flags) = line.strip_prefix("VmFlags:") {
+            if inside {
+                return flags.to_string();
+            }
+        } else if let Some(range) = line.split_whitespace().next().filter(|r| r.contains('-') && !r.ends_with(':')) {
+            let mut bounds = range.split('-').map(|n| usize::from_str_radix(n, 16).unwrap());
+            let (start, end) = (bounds.next().unwrap(), bounds.next().unwrap());
+            inside = start <= addr && addr < end;
+        }
+    }
+
+    panic!("no mapping for {:#x}", addr)
+}
+
+#[test]
+fn dontdump_test() {
+    // without `mlock` only the policy excludes the value from core dumps
+    let policy = *Policy::new().lock_memory(false);
+    let k = policy.build([1u8; 32]).unwrap();
+    let page = policy.build([1u8; 4096]).unwrap();
+
+    let key_addr = k.read().as_ptr() as usize;
+    let page_addr = page.read().as_ptr() as usize;
+    assert!(vm_flags(key_addr).contains(" dd"));
+    assert!(vm_flags(page_addr).contains(" dd"));
+
+    // a page sized value starts on a page, its canary is on the one before
+    assert_eq!(0, page_addr % 4096);
+    assert!(vm_flags(page_addr - 16).contains(" dd"));
+
+    // `memsec` advised the canary page too, the policy undoes it
+    let dump = Policy::new().lock_memory(false).dump_exclude(false).build([1u8; 4096]).unwrap();
+    let dump_addr = dump.read().as_ptr() as usize;
+    assert!(!vm_flags(dump_addr).contains(" dd"));
+    assert!(!vm_flags(dump_addr - 16).contains(" dd"));
+
+    let mut vec = SecVec::new().unwrap();
+    vec.extend_from_slice(&[1; 5000]);
+    let vec_addr = vec.read().as_ptr() as usize;
+    assert!(vm_flags(vec_addr).contains(" dd"));
+    assert!(vm_flags(vec_addr + 4999).contains(" dd"));
+}
+
+#[test]
+fn harden_process_test() {
+    // in a child, the other tests read `/proc/self`
+    assert_exit_ok(in_child(|| unsafe {
+        let hardening = harden_process();
+        let mut rlim: libc::rlimit = std::mem::zeroed();
+        libc::getrlimit(libc::RLIMIT_CORE, &mut rlim);
+
+        hardening.is_complete()
+            && libc::prctl(libc::PR_GET_DUMPABLE) == 0
+            && rlim.rlim_cur == 0
+            && rlim.rlim_max == 0
+    }));
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+
+use seckey::{ SecKey, SyncSecKey, SealedKey, Policy, Idle };
+
+
+#[test]
+fn policy_idle_readonly_test() {
+    let mut k = Policy::new()
+        .idle(Idle::ReadOnly)
+        .build([1u8; 8])
+        .unwrap();
+
+    let ptr = k.read().as_ptr();
+    k.write()[0] = 0;
+    assert_eq!([0, 1, 1, 1, 1, 1, 1, 1], *k.read());
+
+    // still readable without a guard
+    assert_eq!(1, unsafe { *ptr.add(1) });
+}
+
+#[test]
+fn policy_options_test() {
+    let mut policy = Policy::new();
+    policy.lock_memory(false).dump_exclude(false);
+
+    let mut unprotected = [1u8; 4];
+    let k = policy.build_from_bytes(&mut unprotected).unwrap();
+    assert_eq!([0; 4], unprotected);
+    assert_eq!([1; 4], *k.read());
+
+    let k: SecKey<u64> = unsafe { policy.build_with(|p| *p = 7).unwrap() };
+    assert_eq!(7, *k.read());
+
+    assert_eq!(Policy::default(), Policy::new());
+}
+
+#[test]
+fn policy_wrapped_test() {
+    let k = Policy::new().idle(Idle::ReadOnly).build(3u32).unwrap();
+    let k = SyncSecKey::from(k);
+    assert_eq!(3, *k.read());
+    *k.write() = 4;
+    assert_eq!(4, *k.read());
+
+    let k = Policy::new().idle(Idle::ReadOnly).build([5u8; 16]).unwrap();
+    let mut k = SealedKey::from(k);
+    k.write()[0] = 0;
+    assert_eq!(0, k.read()[0]);
+    assert_eq!(5, k.read()[15]);
+}
@@ -0,0 +1,123 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+#[cfg(unix)] extern crate libc;
+
+mod common;
+
+use common::{ run_child, is_child };
+use seckey::SecPool;
+
+
+#[test]
+fn pool_alloc_test() {
+    let pool = SecPool::new(32).unwrap();
+
+    let mut keys = (0..300u16)
+        .map(|i| pool.alloc([i as u8; 32]).unwrap())
+        .collect::<Vec<_>>();
+
+    for (i, key) in keys.iter_mut().enumerate() {
+        key.write()[0] = !(i as u8);
+    }
+
+    // readers and writers of the same chunk
+    {
+        let (k0, rest) = keys.split_at_mut(1);
+        let r0 = k0[0].read();
+        rest[0].write()[1] = 0;
+        assert_eq!(255, r0[0]);
+    }
+
+    for (i, key) in keys.iter().enumerate().skip(2) {
+        let rkey = key.read();
+        assert_eq!(!(i as u8), rkey[0]);
+        assert_eq!(i as u8, rkey[31]);
+    }
+    assert_eq!(0, keys[1].read()[1]);
+}
+
+#[test]
+fn pool_reuse_test() {
+    let pool = SecPool::new(8).unwrap();
+
+    let k1 = pool.alloc(1u64).unwrap();
+    let k2 = pool.alloc(2u64).unwrap();
+    let p1 = format!("{:p}", k1);
+
+    // small keys share a page
+    let a1 = usize::from_str_radix(&p1[2..], 16).unwrap();
+    let a2 = usize::from_str_radix(&format!("{:p}", k2)[2..], 16).unwrap();
+    assert!(a1.abs_diff(a2) < 4096);
+
+    drop(k1);
+    let k3 = pool.alloc(3u64).unwrap();
+    assert_eq!(p1, format!("{:p}", k3));
+    assert_eq!(3, *k3.read());
+    assert_eq!(2, *k2.read());
+}
+
+#[test]
+#[should_panic]
+fn pool_oversized_test() {
+    let pool = SecPool::new(16).unwrap();
+    let _ = pool.alloc([0u8; 17]);
+}
+
+#[test]
+fn pool_underflow_child() {
+    if !is_child() {
+        return;
+    }
+
+    let pool = SecPool::new(16).unwrap();
+    let _k1 = pool.alloc([0u8; 16]).unwrap();
+    let mut k2 = pool.alloc([0u8; 16]).unwrap();
+    let mut wk = k2.write();
+
+    unsafe {
+        let p = wk.as_mut_ptr().sub(1);
+        *p = !*p;
+    }
+
+    drop(wk);
+    unreachable!();
+}
+
+#[test]
+fn pool_underflow_test() {
+    let output = run_child("pool_underflow_child");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("pool canary check failed"), "{}", stderr);
+    assert!(stderr.contains("slot:    1"), "{}", stderr);
+}
+
+#[test]
+fn pool_readonly_child() {
+    if !is_child() {
+        return;
+    }
+
+    let pool = SecPool::new(16).unwrap();
+    let k1 = pool.alloc([1u8; 16]).unwrap();
+    let mut k2 = pool.alloc([2u8; 16]).unwrap();
+
+    let rk = k1.read();
+    k2.write()[0] = 0;
+
+    // the write guard is gone, the page is only readable again
+    assert_eq!(1, rk[0]);
+    unsafe { *(rk.as_ptr() as *mut u8) = 0 };
+    unreachable!();
+}
+
+#[test]
+#[cfg(unix)]
+fn pool_readonly_test() {
+    use std::os::unix::process::ExitStatusExt;
+
+    let output = run_child("pool_readonly_child");
+    assert_eq!(Some(11), output.status.signal(), "{}", String::from_utf8_lossy(&output.stderr));
+}
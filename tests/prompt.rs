@@ -0,0 +1,105 @@
+#![cfg(all(feature = "tty", unix))]
+
+extern crate seckey;
+extern crate libc;
+
+mod common;
+
+use std::{ io, mem, ptr, thread };
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::time::Duration;
+use common::{ spawn_child, wait_child, assert_exit_ok };
+use seckey::prompt::{ read_password, read_password_from };
+
+
+#[test]
+fn prompt_backspace_test() {
+    let pass = read_password_from(&b"hunter3\x7f2\n"[..]).unwrap();
+    assert_eq!(&*pass.read(), "hunter2");
+
+    // a multi-byte char is removed as a whole
+    let pass = read_password_from("pä\x08ss\r".as_bytes()).unwrap();
+    assert_eq!(&*pass.read(), "pss");
+
+    let pass = read_password_from(&b"\x7fwrong\x15right"[..]).unwrap();
+    assert_eq!(&*pass.read(), "right");
+
+    let pass = read_password_from(&b""[..]).unwrap();
+    assert_eq!(&*pass.read(), "");
+}
+
+#[test]
+fn prompt_utf8_test() {
+    let err = read_password_from(&b"\xff\n"[..]).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn prompt_signal_test() {
+    static HANDLED: AtomicBool = AtomicBool::new(false);
+
+    extern "C" fn handler(_: libc::c_int) {
+        HANDLED.store(true, Ordering::SeqCst);
+    }
+
+    let (mut master, mut slave) = (0, 0);
+    unsafe {
+        assert_eq!(0, libc::openpty(&mut master, &mut slave, ptr::null_mut(), ptr::null(), ptr::null()));
+    }
+
+    let pid = spawn_child(|| unsafe {
+        // the pty becomes the controlling terminal, `/dev/tty`
+        libc::close(master);
+        libc::setsid();
+        libc::ioctl(slave, libc::TIOCSCTTY, 0);
+
+        // the application handles `SIGINT` and carries on
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = handler as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigaction(libc::SIGINT, &action, ptr::null_mut());
+
+        match read_password("") {
+            Ok(pass) => HANDLED.load(Ordering::SeqCst) && &*pass.read() == "hunter2",
+            Err(_) => false
+        }
+    });
+
+    let echo = || unsafe {
+        let mut termios: libc::termios = mem::zeroed();
+        assert_eq!(0, libc::tcgetattr(slave, &mut termios));
+        termios.c_lflag & libc::ECHO != 0
+    };
+    let wait_for = |echo_on: bool| {
+        for _ in 0..500 {
+            if echo() == echo_on {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("echo is still {}", !echo_on);
+    };
+
+    // the signal arrives while the prompt reads
+    wait_for(false);
+    thread::sleep(Duration::from_millis(50));
+    unsafe { libc::kill(pid, libc::SIGINT) };
+    thread::sleep(Duration::from_millis(200));
+    assert!(!echo(), "echo is on after a handled signal");
+
+    unsafe {
+        assert_eq!(8, libc::write(master, b"hunter2\n".as_ptr() as *const libc::c_void, 8));
+    }
+    assert_exit_ok(wait_child(pid));
+
+    // only the newline after the prompt reached the terminal
+    let mut output = [0u8; 64];
+    let len = unsafe { libc::read(master, output.as_mut_ptr() as *mut libc::c_void, output.len()) };
+    assert!(len > 0);
+    assert!(!output[..len as usize].windows(7).any(|w| w == b"hunter2"));
+
+    unsafe {
+        libc::close(master);
+        libc::close(slave);
+    }
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+#[cfg(feature = "rand_core")] extern crate rand_core;
+
+use seckey::SecKey;
+
+
+#[test]
+fn random_test() {
+    let k1 = SecKey::<[u8; 32]>::random().unwrap();
+    let k2 = SecKey::<[u8; 32]>::random().unwrap();
+    assert_ne!(*k1.read(), *k2.read());
+
+    let k = SecKey::<[u8]>::random(1000).unwrap();
+    assert_eq!(1000, k.read().len());
+    assert!(k.read().iter().any(|&b| b != 0));
+
+    let k = SecKey::<[u8]>::random(0).unwrap();
+    assert!(k.read().is_empty());
+}
+
+#[cfg(feature = "rand_core")]
+#[test]
+fn random_rng_test() {
+    use rand_core::{ RngCore, CryptoRng, Error, impls };
+
+    struct CountingRng(u64);
+
+    impl RngCore for CountingRng {
+        fn next_u32(&mut self) -> u32 {
+            self.next_u64() as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 += 1;
+            self.0
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            impls::fill_bytes_via_next(self, dest)
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    impl CryptoRng for CountingRng {}
+
+    let k = SecKey::<[u8; 8]>::from_rng(&mut CountingRng(0)).unwrap();
+    assert_eq!(*k.read(), [1, 0, 0, 0, 0, 0, 0, 0]);
+
+    let k = SecKey::<[u8]>::from_rng(16, &mut CountingRng(0)).unwrap();
+    assert_eq!(*k.read(), [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0]);
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+
+use std::{ env, fs, process };
+use std::io::{ self, Read };
+use std::path::PathBuf;
+use seckey::{ SecKey, SecretFile };
+
+
+struct Interrupting<'a>(&'a [u8], bool);
+
+impl<'a> Read for Interrupting<'a> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.1 = !self.1;
+        if self.1 {
+            return Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"));
+        }
+
+        // one byte at a time
+        let n = if self.0.is_empty() || buf.is_empty() { 0 } else { 1 };
+        buf[..n].copy_from_slice(&self.0[..n]);
+        self.0 = &self.0[n..];
+        Ok(n)
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("seckey-{}-{}", process::id(), name))
+}
+
+
+#[test]
+fn reader_test() {
+    let data = (0..1000).map(|i| i as u8).collect::<Vec<_>>();
+
+    let k = SecKey::from_reader(&data[..], 1000).unwrap();
+    assert_eq!(&*k.read(), &data[..]);
+
+    let k = SecKey::from_reader(Interrupting(&data[..10], false), 10).unwrap();
+    assert_eq!(&*k.read(), &data[..10]);
+
+    let err = SecKey::from_reader(&data[..], 999).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    let k = SecKey::from_reader(&[][..], 0).unwrap();
+    assert!(k.read().is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn reader_file_test() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = temp_path("key");
+    fs::write(&path, b"secret").unwrap();
+
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+    let err = SecKey::read_file(&path).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+    let k = SecretFile::new().allow_permissive(true).read(&path).unwrap();
+    assert_eq!(*k.read(), *b"secret");
+
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o400)).unwrap();
+    let k = SecKey::read_file(&path).unwrap();
+    assert_eq!(*k.read(), *b"secret");
+
+    let err = SecretFile::new().max_len(5).read(&path).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+
+use seckey::{ SecKey, SealedKey, SecReadGuard, Policy, Idle };
+
+
+#[test]
+fn sealedkey_read_then_read() {
+    let secpass = SealedKey::new(1).unwrap();
+
+    let rpass1 = secpass.read();
+    let rpass2 = secpass.read();
+
+    assert_eq!(1, *rpass1);
+    assert_eq!(1, *rpass2);
+
+    drop(rpass1);
+
+    assert_eq!(1, *rpass2);
+}
+
+#[test]
+fn sealedkey_reseal() {
+    let mut secpass = SealedKey::new([7u8; 100]).unwrap();
+
+    for i in 0..100 {
+        secpass.write()[i] = i as u8;
+        assert_eq!(i as u8, secpass.read()[i]);
+    }
+
+    let expected = (0..100).map(|i| i as u8).collect::<Vec<_>>();
+    assert_eq!(&expected[..], &secpass.read()[..]);
+}
+
+#[test]
+fn sealedkey_unsized() {
+    let mut unprotected = "hunter2".to_string();
+    let secpass = SealedKey::from(SecKey::from_str(&mut unprotected).unwrap());
+
+    let head = SecReadGuard::map(secpass.read(), |s| &s[..6]);
+    assert_eq!("hunter", &*head);
+    drop(head);
+
+    assert_eq!("hunter2", &*secpass.read());
+}
+
+#[test]
+fn sealedkey_drop() {
+    // `Vec` must be unsealed before it is dropped
+    let mut secpass = SealedKey::new(vec![1u8, 2, 3]).unwrap_or_else(|_| panic!());
+    secpass.write().push(4);
+    assert_eq!(vec![1, 2, 3, 4], *secpass.read());
+}
+
+#[test]
+fn sealedkey_idle_test() {
+    // a readable idle key, so the sealed bytes can be looked at
+    fn sealed(key: &SealedKey<[u8; 64]>) -> [u8; 64] {
+        let addr = usize::from_str_radix(format!("{:p}", *key).trim_start_matches("0x"), 16).unwrap();
+        unsafe { *(addr as *const [u8; 64]) }
+    }
+
+    let policy = *Policy::new().idle(Idle::ReadOnly);
+    let k1 = SealedKey::from(policy.build([7u8; 64]).unwrap());
+    let k2 = SealedKey::from(policy.build([7u8; 64]).unwrap());
+
+    let sealed1 = sealed(&k1);
+    assert_ne!([7; 64], sealed1);
+    assert_eq!([7; 64], *k1.read());
+
+    // every seal takes a fresh nonce
+    assert_ne!(sealed1, sealed(&k1));
+    assert_ne!(sealed(&k1), sealed(&k2));
+}
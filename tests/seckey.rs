@@ -2,7 +2,12 @@
 #![cfg(feature = "use_std")]
 
 extern crate seckey;
+#[cfg(unix)] extern crate libc;
 
+mod common;
+
+#[cfg(unix)] use std::ptr;
+#[cfg(unix)] use common::{ in_child, assert_fault };
 use seckey::SecKey;
 
 
@@ -81,3 +86,104 @@ fn test_seckey_ref() {
 
     assert_eq!(bar.0, 0x42);
 }
+
+#[test]
+fn seckey_error_test() {
+    use seckey::SecKeyError;
+
+    assert_eq!(SecKeyError::AllocFailed.errno(), None);
+    assert_eq!(SecKeyError::AllocFailed.to_string(), "secure allocation failed");
+
+    let err = SecKeyError::LockFailed(Some(12));
+    assert_eq!(err.errno(), Some(12));
+    assert!(err.to_string().starts_with("failed to lock secure memory: "));
+}
+
+#[test]
+fn seckey_map_guard_test() {
+    use seckey::{ SecReadGuard, SecWriteGuard };
+
+    let mut key = SecKey::new(([1u8; 4], [2u32; 2])).unwrap();
+
+    {
+        let rpass = key.read();
+        let second = SecReadGuard::map(key.read(), |k| &k.1);
+        drop(rpass);
+
+        let last = SecReadGuard::map(second, |k| &k[1]);
+        assert_eq!(*last, 2);
+    }
+
+    {
+        let mut first = SecWriteGuard::map(key.write(), |k| &mut k.0[..]);
+        first[1] = 0;
+        assert_eq!(*first, [1, 0, 1, 1]);
+    }
+
+    assert_eq!(*key.read(), ([1, 0, 1, 1], [2, 2]));
+}
+
+#[test]
+fn seckey_scoped_panic_test() {
+    use std::panic::{ self, AssertUnwindSafe };
+
+    let mut key = SecKey::new([1u8; 8]).unwrap();
+
+    let r = panic::catch_unwind(AssertUnwindSafe(|| {
+        key.with_write(|k| {
+            k[0] = 0;
+            panic!("oops")
+        })
+    }));
+    assert!(r.is_err());
+
+    let r = panic::catch_unwind(AssertUnwindSafe(|| key.with_read(|_| panic!("oops"))));
+    assert!(r.is_err());
+
+    // both guards are gone, the key can be borrowed again
+    key.write()[1] = 2;
+    assert_eq!([0, 2, 1, 1, 1, 1, 1, 1], *key.read());
+
+    // and it is protected again while idle
+    #[cfg(unix)]
+    {
+        let addr = usize::from_str_radix(format!("{:p}", key).trim_start_matches("0x"), 16).unwrap();
+        assert_fault(in_child(|| unsafe { ptr::read_volatile(addr as *const u8) == 0 }));
+    }
+}
+
+#[test]
+#[allow(clippy::mutable_key_type)]
+fn seckey_eq_hash_test() {
+    use std::collections::HashMap;
+    use std::hash::{ Hash, Hasher };
+    use std::collections::hash_map::DefaultHasher;
+
+    fn hash<T: Hash + ?Sized>(t: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        t.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    let k1 = SecKey::from_bytes(&mut [1u8, 2, 3][..]).unwrap();
+    let k2 = SecKey::from_bytes(&mut [1u8, 2, 3][..]).unwrap();
+    let k3 = SecKey::from_bytes(&mut [1u8, 2][..]).unwrap();
+
+    assert!(bool::from(k1.ct_eq(&[1, 2, 3])));
+    assert!(bool::from(k1.ct_eq_key(&k2)));
+    assert!(bool::from(k1.ct_eq_key(&k1)));
+    assert!(!bool::from(k1.ct_eq_key(&k3)));
+    assert!(k1 == k2);
+    assert!(k1 != k3);
+    assert!(k1 == [1, 2, 3][..]);
+
+    assert_eq!(hash(&k1), hash(&k2));
+    assert_ne!(hash(&k1), hash(&k3));
+    // the secret itself is not hashed
+    assert_ne!(hash(&k1), hash(&[1u8, 2, 3][..]));
+
+    let mut map = HashMap::new();
+    map.insert(k1, "k1");
+    map.insert(k3, "k3");
+    assert_eq!(map.get(&k2), Some(&"k1"));
+}
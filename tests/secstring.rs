@@ -0,0 +1,63 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+
+use seckey::{ SecKey, SecVec, SecString };
+
+
+#[test]
+fn secstring_push_pop() {
+    let mut s = SecString::new().unwrap();
+    assert_eq!(s.pop(), None);
+
+    for ch in "pässwörd💖".chars() {
+        s.push(ch);
+    }
+    assert_eq!(&*s.read(), "pässwörd💖");
+
+    assert_eq!(s.pop(), Some('💖'));
+    assert_eq!(s.pop(), Some('d'));
+    assert_eq!(&*s.read(), "pässwör");
+
+    s.truncate(3);
+    assert_eq!(&*s.read(), "pä");
+
+    s.clear();
+    assert!(s.is_empty());
+}
+
+#[test]
+#[should_panic]
+fn secstring_truncate_char_boundary() {
+    let mut s = SecString::new().unwrap();
+    s.push_str("ä");
+    s.truncate(1);
+}
+
+#[test]
+fn secstring_from_utf8() {
+    let mut buf = SecVec::new().unwrap();
+    buf.extend_from_slice(b"abc\xff");
+
+    let mut buf = SecString::from_utf8(buf).unwrap_err();
+    buf.truncate(3);
+
+    let s = SecString::from_utf8(buf).unwrap();
+    assert_eq!(&*s.read(), "abc");
+
+    let buf = s.into_bytes();
+    assert_eq!(&*buf.read(), b"abc");
+}
+
+#[test]
+fn secstring_seckey_convert() {
+    let mut unprotected = "abc".to_string();
+    let key = SecKey::from_str(&mut unprotected).unwrap();
+
+    let mut s = SecString::from(key);
+    s.push_str("def");
+    s.write().make_ascii_uppercase();
+
+    let key = s.into_seckey();
+    assert_eq!(&*key.read(), "ABCDEF");
+}
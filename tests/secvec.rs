@@ -0,0 +1,64 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+
+use seckey::{ SecKey, SecKeyError, SecVec };
+
+
+#[test]
+fn secvec_push_and_grow() {
+    let mut buf = SecVec::new().unwrap();
+    assert!(buf.is_empty());
+
+    for i in 0..5000 {
+        buf.push(i as u8);
+    }
+
+    assert_eq!(buf.len(), 5000);
+    assert!(buf.capacity() >= 5000);
+    assert!(buf.read().iter().enumerate().all(|(i, &b)| b == i as u8));
+}
+
+#[test]
+fn secvec_extend_truncate() {
+    let mut buf = SecVec::with_capacity(4).unwrap();
+    buf.extend_from_slice(b"pass");
+    assert_eq!(buf.capacity(), 4);
+
+    buf.extend_from_slice(b"phrase");
+    assert_eq!(&*buf.read(), b"passphrase");
+
+    buf.truncate(4);
+    assert_eq!(&*buf.read(), b"pass");
+
+    buf.truncate(10);
+    assert_eq!(&*buf.read(), b"pass");
+
+    buf.write()[0] = b'b';
+    assert_eq!(&*buf.read(), b"bass");
+
+    buf.clear();
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn secvec_reserve() {
+    let mut buf = SecVec::new().unwrap();
+    buf.reserve(100);
+    assert!(buf.capacity() >= 100);
+    assert!(buf.is_empty());
+
+    assert_eq!(buf.try_reserve(usize::MAX), Err(SecKeyError::AllocFailed));
+}
+
+#[test]
+fn secvec_seckey_convert() {
+    let mut unprotected = [1u8, 2, 3];
+    let key = SecKey::from_bytes(&mut unprotected).unwrap();
+
+    let mut buf = SecVec::from(key);
+    buf.push(4);
+
+    let key = buf.into_seckey();
+    assert_eq!(*key.read(), [1, 2, 3, 4]);
+}
@@ -0,0 +1,61 @@
+#![cfg(all(feature = "serde", feature = "use_std"))]
+
+extern crate serde_json;
+extern crate seckey;
+
+use serde_json::Value;
+use seckey::{ SecKey, Exposed };
+
+
+#[test]
+fn serde_bytes_test() {
+    let key: SecKey<[u8]> = serde_json::from_str("[1, 2, 3]").unwrap();
+    assert_eq!(*key.read(), [1, 2, 3]);
+
+    let key: SecKey<[u8]> = serde_json::from_str("\"abc\"").unwrap();
+    assert_eq!(*key.read(), *b"abc");
+
+    let key: SecKey<[u8]> = serde_json::from_str("[]").unwrap();
+    assert!(key.read().is_empty());
+
+    assert!(serde_json::from_str::<SecKey<[u8]>>("[1, 256]").is_err());
+}
+
+#[test]
+fn serde_str_test() {
+    let key: SecKey<str> = serde_json::from_str("\"hunter2\"").unwrap();
+    assert_eq!(&*key.read(), "hunter2");
+
+    // escapes are copied through the deserializer's scratch buffer
+    let key: SecKey<str> = serde_json::from_str("\"hunter\\u0032\"").unwrap();
+    assert_eq!(&*key.read(), "hunter2");
+
+    // a `Value` hands over an owned `String`
+    let key: SecKey<str> = serde_json::from_value(Value::String("hunter2".into())).unwrap();
+    assert_eq!(&*key.read(), "hunter2");
+
+    assert!(serde_json::from_str::<SecKey<str>>("[1, 2]").is_err());
+}
+
+#[test]
+fn serde_array_test() {
+    let key: SecKey<[u8; 4]> = serde_json::from_str("[1, 2, 3, 4]").unwrap();
+    assert_eq!(*key.read(), [1, 2, 3, 4]);
+
+    assert!(serde_json::from_str::<SecKey<[u8; 4]>>("[1, 2, 3]").is_err());
+    assert!(serde_json::from_str::<SecKey<[u8; 4]>>("[1, 2, 3, 4, 5]").is_err());
+}
+
+#[test]
+fn serde_exposed_test() {
+    let key = SecKey::new([1u8, 2, 3]).unwrap();
+    let json = serde_json::to_string(&Exposed(&key)).unwrap();
+    assert_eq!(json, "[1,2,3]");
+
+    let key: SecKey<[u8; 3]> = serde_json::from_str(&json).unwrap();
+    assert_eq!(*key.read(), [1, 2, 3]);
+
+    let mut pass = "hunter2".to_string();
+    let key = SecKey::from_str(&mut pass).unwrap();
+    assert_eq!(serde_json::to_string(&Exposed(&key)).unwrap(), "\"hunter2\"");
+}
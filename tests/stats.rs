@@ -0,0 +1,88 @@
+#![cfg(feature = "stats")]
+
+extern crate seckey;
+extern crate libc;
+
+#[cfg(unix)] mod common;
+
+#[cfg(unix)] use std::mem;
+#[cfg(unix)] use std::sync::atomic::{ AtomicUsize, Ordering };
+#[cfg(unix)] use common::{ in_child, assert_exit_ok };
+use seckey::{ SecKey, Policy, stats };
+
+
+#[allow(dead_code)]
+struct Probe([u8; 100]);
+
+fn probe_stats() -> Option<stats::TypeStats> {
+    stats::snapshot().types.into_iter()
+        .find(|t| t.type_name.ends_with("::Probe"))
+}
+
+#[test]
+fn stats_snapshot_test() {
+    let keys = (0..3)
+        .map(|_| SecKey::new(Probe([0; 100])).unwrap_or_else(|_| panic!()))
+        .collect::<Vec<_>>();
+
+    let probe = probe_stats().unwrap();
+    assert_eq!(3, probe.count);
+    assert_eq!(300, probe.bytes);
+    assert_eq!(3, probe.locked_pages);
+
+    let snapshot = stats::snapshot();
+    assert!(snapshot.live >= 3);
+    assert!(snapshot.mapped_pages >= snapshot.locked_pages + 3 * 3);
+    assert!(snapshot.to_string().contains("::Probe"));
+
+    drop(keys);
+    assert!(probe_stats().is_none());
+}
+
+#[test]
+fn stats_unlocked_test() {
+    #[allow(dead_code)]
+    struct Unlocked([u8; 100]);
+
+    let _key = Policy::new()
+        .lock_memory(false)
+        .build(Unlocked([0; 100]))
+        .unwrap_or_else(|_| panic!());
+
+    let unlocked = stats::snapshot().types.into_iter()
+        .find(|t| t.type_name.ends_with("::Unlocked"))
+        .unwrap();
+    assert_eq!(1, unlocked.count);
+    assert_eq!(0, unlocked.locked_pages);
+}
+
+#[cfg(unix)]
+#[test]
+fn stats_limit_hook_test() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn hook(snapshot: &stats::Snapshot) {
+        assert!(snapshot.near_memlock_limit());
+        CALLS.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // the limit is lowered in a child process only
+    assert_exit_ok(in_child(|| unsafe {
+        stats::set_limit_hook(hook);
+
+        // the next key crosses the threshold
+        let snapshot = stats::snapshot();
+        let mut rlim: libc::rlimit = mem::zeroed();
+        libc::getrlimit(libc::RLIMIT_MEMLOCK, &mut rlim);
+        rlim.rlim_cur = ((snapshot.locked_bytes() + snapshot.page_size as u64) * 100 / 90) as libc::rlim_t;
+        libc::setrlimit(libc::RLIMIT_MEMLOCK, &rlim);
+
+        let quiet = CALLS.load(Ordering::Relaxed) == 0;
+        let key = SecKey::new([0u8; 32]);
+        let once = CALLS.load(Ordering::Relaxed) == 1;
+
+        // still above, no second report
+        let _ = SecKey::new([0u8; 32]);
+        quiet && key.is_ok() && once && CALLS.load(Ordering::Relaxed) == 1
+    }));
+}
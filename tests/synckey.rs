@@ -0,0 +1,115 @@
+#![cfg(feature = "use_std")]
+
+extern crate seckey;
+
+use std::sync::Arc;
+use std::sync::atomic::{ AtomicBool, Ordering };
+use std::thread;
+use std::time::Duration;
+use seckey::SyncSecKey;
+
+
+#[test]
+fn synckey_read_then_read() {
+    let secpass = SyncSecKey::new(1).unwrap();
+
+    let rpass1 = secpass.read();
+    let rpass2 = secpass.read();
+
+    assert_eq!(1, *rpass1);
+    assert_eq!(1, *rpass2);
+
+    drop(rpass1);
+
+    assert_eq!(1, *rpass2);
+}
+
+#[test]
+fn synckey_concurrent_read() {
+    let secpass = Arc::new(SyncSecKey::new([42u8; 32]).unwrap());
+
+    let handles = (0..8)
+        .map(|_| {
+            let secpass = secpass.clone();
+            thread::spawn(move || {
+                for _ in 0..1000 {
+                    assert_eq!([42u8; 32], *secpass.read());
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn synckey_write_excludes_read() {
+    let secpass = Arc::new(SyncSecKey::new([0u64; 4]).unwrap());
+    let done = Arc::new(AtomicBool::new(false));
+
+    let reader = {
+        let secpass = secpass.clone();
+        let done = done.clone();
+        thread::spawn(move || {
+            while !done.load(Ordering::Acquire) {
+                let rpass = secpass.read();
+                assert!(rpass.iter().all(|&n| n == rpass[0]));
+            }
+        })
+    };
+
+    for i in 1..1000 {
+        let mut wpass = secpass.write();
+        for n in wpass.iter_mut() {
+            *n = i;
+        }
+    }
+    done.store(true, Ordering::Release);
+    reader.join().unwrap();
+
+    assert_eq!([999; 4], *secpass.read());
+}
+
+#[test]
+fn synckey_owned_guard_test() {
+    use seckey::SecOwnedWriteGuard;
+
+    let key = Arc::new(SyncSecKey::new([1u8; 4]).unwrap());
+    let view = key.read_owned();
+    assert_eq!(*key.read(), [1; 4]);
+
+    let view = thread::spawn(move || {
+        assert_eq!(*view, [1; 4]);
+        view
+    }).join().unwrap();
+
+    // the key is shared, the writer waits for the reader
+    let writer = {
+        let key = key.clone();
+        thread::spawn(move || key.write_owned()[3] = 4)
+    };
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(*view, [1; 4]);
+    drop(view);
+    writer.join().unwrap();
+    assert_eq!(*key.read_owned(), [1, 1, 1, 4]);
+
+    let mut wpass = key.clone().write_owned();
+    wpass[0] = 0;
+    let key2 = SecOwnedWriteGuard::into_arc(wpass);
+    assert!(Arc::ptr_eq(&key, &key2));
+    assert_eq!(*key.read(), [0, 1, 1, 4]);
+}
+
+#[test]
+fn synckey_owned_guard_send() {
+    use seckey::{ SecOwnedReadGuard, SecOwnedWriteGuard };
+
+    fn assert_send<T: Send>() {}
+
+    assert_send::<SecOwnedReadGuard<[u8; 4]>>();
+    assert_send::<SecOwnedWriteGuard<[u8; 4]>>();
+    assert_send::<SecOwnedReadGuard<[u8]>>();
+}
@@ -2,8 +2,10 @@
 
 
 extern crate seckey;
+#[cfg(feature = "subtle")] extern crate subtle;
 
-use seckey::{ TempKey, CmpKey };
+use std::cmp::Ordering;
+use seckey::{ TempKey, CmpKey, Choice, CtOrd };
 
 
 #[test]
@@ -13,14 +15,119 @@ fn cmpkey_cmp_test() {
     assert_eq!(CmpKey(0), 0);
     assert_ne!(CmpKey(1), 0);
 
-    assert!(CmpKey(-1) > 0);
-        // ^- NOTE 4294967295 > 0
+    assert!(CmpKey(-1) < 0);
+    assert!(CmpKey(256u32) > 1);
+    assert!(CmpKey(i64::MIN) < i64::MAX);
+    assert!(CmpKey(u128::MAX) > 0);
+
+    // the raw memory order is still available
+    #[cfg(target_endian = "little")]
+    assert_eq!(CmpKey(1u16).bytes_cmp(&256), Ordering::Greater);
+    #[cfg(target_endian = "big")]
+    assert_eq!(CmpKey(1u16).bytes_cmp(&256), Ordering::Less);
+    assert_eq!(CmpKey::from(&[2u8; 3][..]).bytes_cmp(&[1; 4][..]), Ordering::Greater);
 
     let a = [2; 3];
     let b = [1; 4];
     assert_eq!(&a[..] > &b[..], CmpKey::from(&a[..]) > CmpKey::from(&b[..]));
 }
 
+#[test]
+fn cmpkey_ctord_test() {
+    for &a in &[i16::MIN, -257, -256, -1, 0, 1, 255, 256, i16::MAX] {
+        for &b in &[i16::MIN, -257, -256, -1, 0, 1, 255, 256, i16::MAX] {
+            assert_eq!(CmpKey(a).cmp(&CmpKey(b)), a.cmp(&b));
+            assert_eq!(CmpKey(a as u16).cmp(&CmpKey(b as u16)), (a as u16).cmp(&(b as u16)));
+            assert_eq!(bool::from(a.ct_lt(&b)), a < b);
+            assert_eq!(bool::from(a.ct_gt(&b)), a > b);
+        }
+    }
+
+    for a in 0..=255u8 {
+        for b in 0..=255u8 {
+            assert_eq!(bool::from(a.ct_lt(&b)), a < b);
+            assert_eq!(bool::from((a as i8).ct_lt(&(b as i8))), (a as i8) < (b as i8));
+        }
+    }
+
+    let a = [1u32, 256];
+    let b = [1u32, 2, 3];
+    assert_eq!(CmpKey::from(&a[..]).cmp(CmpKey::from(&b[..])), a[..].cmp(&b[..]));
+    assert_eq!(CmpKey::from("abc").cmp(CmpKey::from("abd")), Ordering::Less);
+}
+
+#[test]
+fn cmpkey_eq_padded_test() {
+    let tag = [7u8; 16];
+
+    assert!(bool::from(CmpKey::from(&tag[..]).eq_padded(&[7; 16][..], 16)));
+    assert!(bool::from(CmpKey::from(&tag[..]).eq_padded(&[7; 16][..], 64)));
+    assert!(!bool::from(CmpKey::from(&tag[..]).eq_padded(&[7; 15][..], 64)));
+    assert!(!bool::from(CmpKey::from(&tag[..15]).eq_padded(&[7; 16][..], 64)));
+    assert!(!bool::from(CmpKey::from(&tag[..]).eq_padded(&[7; 16][..], 15)));
+
+    let mut other = tag;
+    other[15] = 0;
+    assert!(!bool::from(CmpKey::from(&tag[..]).eq_padded(&other[..], 64)));
+
+    // trailing zeros do not make a shorter input equal
+    assert!(!bool::from(CmpKey::from(&[1u8, 0][..]).eq_padded(&[1][..], 8)));
+
+    assert!(bool::from(CmpKey::from(&[][..] as &[u8]).eq_padded(&[][..], 8)));
+    assert!(!bool::from(CmpKey::from(&[][..] as &[u8]).eq_padded(&[0][..], 8)));
+}
+
+#[test]
+fn cmpkey_ct_test() {
+    let yes = Choice::from(1);
+    let no = Choice::from(0);
+
+    assert!(bool::from(CmpKey([0u32; 4]).is_zero()));
+    assert!(!bool::from(CmpKey([0u32, 0, 0, 1 << 31]).is_zero()));
+    assert!(bool::from(CmpKey::from(&[][..] as &[u8]).is_zero()));
+
+    assert!(bool::from(CmpKey([1u8, 2]).ct_eq(&[1, 2])));
+    assert!(!bool::from(CmpKey::from(&[1u8, 2][..]).ct_eq(&[1, 2, 3][..])));
+
+    let a = [2u8; 3];
+    let b = [1u8; 4];
+    assert!(bool::from(CmpKey::from(&a[..]).ct_gt(&b[..])));
+    assert!(bool::from(CmpKey::from(&b[..]).ct_lt(&a[..])));
+    assert!(bool::from(CmpKey::from(&a[..2]).ct_lt(&a[..])));
+    assert!(!bool::from(CmpKey::from(&a[..]).ct_lt(&a[..])));
+    assert!(!bool::from(CmpKey::from(&a[..]).ct_gt(&a[..])));
+
+    assert_eq!(CmpKey::ct_select(&[1u8; 4], &[2; 4], no), [1; 4]);
+    assert_eq!(CmpKey::ct_select(&[1u8; 4], &[2; 4], yes), [2; 4]);
+
+    let mut x = [1u16, 2, 3];
+    let mut y = [4u16, 5, 6];
+    CmpKey::from_mut(&mut x[..]).conditional_swap(&mut y[..], no);
+    assert_eq!((x, y), ([1, 2, 3], [4, 5, 6]));
+    CmpKey::from_mut(&mut x[..]).conditional_swap(&mut y[..], yes);
+    assert_eq!((x, y), ([4, 5, 6], [1, 2, 3]));
+
+    assert_eq!((yes & no).unwrap_u8(), 0);
+    assert_eq!((yes | no).unwrap_u8(), 1);
+    assert_eq!((yes ^ yes).unwrap_u8(), 0);
+    assert_eq!((!no).unwrap_u8(), 1);
+}
+
+#[cfg(feature = "subtle")]
+#[test]
+fn cmpkey_subtle_test() {
+    use subtle::ConstantTimeEq;
+
+    let choice: subtle::Choice = CmpKey([1u8; 4]).is_zero().into();
+    assert_eq!(choice.unwrap_u8(), 0);
+
+    let choice: Choice = subtle::Choice::from(1).into();
+    assert_eq!(choice.unwrap_u8(), 1);
+
+    let a = CmpKey([1u8; 4]);
+    assert_eq!(ConstantTimeEq::ct_eq(&a, &CmpKey([1u8; 4])).unwrap_u8(), 1);
+}
+
 #[test]
 fn tempkey_slice_test() {
     // fixed size
@@ -94,3 +201,25 @@ fn tempkey_from_str() {
     assert!(bar2.ends_with("bar"));
     assert_eq!(&bar2[3..][..3], String::from_utf8(vec![0x00, 0x00, 0x00]).unwrap());
 }
+
+#[test]
+fn tempkey_composite_test() {
+    use std::num::Wrapping;
+
+    // X448 scalar
+    let mut key = [42u8; 56 * 2 - 16];
+    {
+        let tempkey = TempKey::from(&mut key);
+        assert_eq!(CmpKey::from(&*tempkey), &[42u8; 96]);
+    }
+    assert_eq!(key, [0; 96]);
+
+    let mut schedule = ([Wrapping(1u32); 60], 2u64, [1.5f32; 3]);
+    {
+        let tempkey = TempKey::from(&mut schedule);
+        assert_eq!((tempkey.0)[59], Wrapping(1));
+    }
+    assert_eq!(schedule.0[..], [Wrapping(0); 60][..]);
+    assert_eq!(schedule.1, 0);
+    assert_eq!(schedule.2, [0.0; 3]);
+}